@@ -1,15 +1,16 @@
+use bj_common::parse_version;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Reply, ReplyOn, Response,
     StdError, StdResult, SubMsg, Uint128, WasmMsg,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{self, Cw20ExecuteMsg, MinterResponse};
 // use cw_utils::{parse_instantiate_response_data, MsgInstantiateContractResponse};
 
 use crate::error::ContractError;
-use crate::msg::{ClaimedResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::msg::{ClaimedResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use crate::parse_reply::{parse_instantiate_response_data, MsgInstantiateContractResponse};
 use crate::querier;
 use crate::state::{State, Stats, STATE, STATS};
@@ -177,6 +178,32 @@ pub fn try_topup(
         .add_attribute("amount", amount.to_string()))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            contract: stored.contract,
+        });
+    }
+    let version = parse_version(&stored.version).ok_or_else(|| ContractError::InvalidVersion {
+        version: stored.version.clone(),
+    })?;
+    if Some(version) > parse_version(CONTRACT_VERSION) {
+        return Err(ContractError::CannotDowngrade {
+            version: stored.version,
+        });
+    }
+
+    // no layout changes yet
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -199,7 +226,8 @@ mod tests {
 
     use super::*;
     use cosmwasm_std::testing::{
-        mock_dependencies_with_balances, mock_env, mock_info, MockApi, MockStorage,
+        mock_dependencies, mock_dependencies_with_balances, mock_env, mock_info, MockApi,
+        MockStorage,
     };
     use cosmwasm_std::{
         coins, from_binary, ContractResult, OwnedDeps, ReplyOn, SubMsg, SubMsgExecutionResponse,
@@ -409,4 +437,41 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn migrate_version_check() {
+        let mut deps = mock_dependencies(&[]);
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(res.attributes[2].value, CONTRACT_VERSION);
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::CannotDowngrade {
+                version: "99.0.0".to_string()
+            }
+        );
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "latest").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::InvalidVersion {
+                version: "latest".to_string()
+            }
+        );
+
+        set_contract_version(deps.as_mut().storage, "crates.io:bj-game", "0.1.0").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::CannotMigrate {
+                contract: "crates.io:bj-game".to_string()
+            }
+        );
+    }
 }
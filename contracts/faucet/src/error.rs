@@ -11,6 +11,15 @@ pub enum ContractError {
 
     #[error("Out of stock")]
     OutOfStock {},
+
+    #[error("Cannot migrate from contract {contract}")]
+    CannotMigrate { contract: String },
+
+    #[error("Cannot migrate from newer version {version}")]
+    CannotDowngrade { version: String },
+
+    #[error("Invalid version {version}")]
+    InvalidVersion { version: String },
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -12,7 +12,8 @@ mod tests {
             crate::contract::instantiate,
             crate::contract::query,
         )
-        .with_reply(crate::contract::reply);
+        .with_reply(crate::contract::reply)
+        .with_migrate(crate::contract::migrate);
         Box::new(contract)
     }
 
@@ -51,7 +52,7 @@ mod tests {
                 &msg,
                 &[],
                 "test",
-                None,
+                Some(ADMIN.to_string()),
             )
             .unwrap();
 
@@ -92,4 +93,58 @@ mod tests {
             app.execute(Addr::unchecked(USER), cosmos_msg).unwrap();
         }
     }
+
+    mod migrate {
+        use super::*;
+        use crate::msg::{ClaimedResponse, ExecuteMsg, MigrateMsg, QueryMsg};
+        use cw2::query_contract_info;
+
+        #[test]
+        fn live_instance() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+
+            let msg = ExecuteMsg::TopUp {
+                amount: Uint128::new(1_000_000),
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+            let msg = ExecuteMsg::Claim {
+                amount: Uint128::new(1_000),
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(USER), cosmos_msg).unwrap();
+
+            let new_id = app.store_code(contract_template());
+            app.migrate_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &MigrateMsg {},
+                new_id,
+            )
+            .unwrap_err();
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &MigrateMsg {},
+                new_id,
+            )
+            .unwrap();
+
+            let info = query_contract_info(&app, cw_template_contract.addr().to_string()).unwrap();
+            assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+
+            // state survives the migration
+            let res: ClaimedResponse = app
+                .wrap()
+                .query_wasm_smart(cw_template_contract.addr(), &QueryMsg::GetClaimed {})
+                .unwrap();
+            assert_eq!(res.claimed, Uint128::new(1_000));
+
+            let msg = ExecuteMsg::Claim {
+                amount: Uint128::new(1_000),
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(USER), cosmos_msg).unwrap();
+        }
+    }
 }
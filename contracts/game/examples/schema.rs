@@ -3,7 +3,13 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use bj_game::msg::{DepositResponse, ExecuteMsg, GameStateResponce, InstantiateMsg, QueryMsg};
+use bj_game::msg::{
+    BankrollResponse, ConfigResponse, DepositResponse, ExecuteMsg, GameStateResponce,
+    HistoryResponse, HouseChainResponse, HouseSeedResponse, InstantiateMsg, LeaderboardResponse,
+    LiquidityResponse, MigrateMsg, QueryMsg, ShoeResponse, StandingsResponse, StateResponse,
+    StatsResponse, TableResponse, TablesResponse, TokensResponse, TournamentEntryResponse,
+    TournamentResponse, VerifyRoundResponse,
+};
 use bj_game::state::State;
 
 fn main() {
@@ -15,7 +21,25 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
     export_schema(&schema_for!(DepositResponse), &out_dir);
     export_schema(&schema_for!(GameStateResponce), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(TokensResponse), &out_dir);
+    export_schema(&schema_for!(StateResponse), &out_dir);
+    export_schema(&schema_for!(ShoeResponse), &out_dir);
+    export_schema(&schema_for!(BankrollResponse), &out_dir);
+    export_schema(&schema_for!(LiquidityResponse), &out_dir);
+    export_schema(&schema_for!(HistoryResponse), &out_dir);
+    export_schema(&schema_for!(StatsResponse), &out_dir);
+    export_schema(&schema_for!(LeaderboardResponse), &out_dir);
+    export_schema(&schema_for!(TableResponse), &out_dir);
+    export_schema(&schema_for!(TablesResponse), &out_dir);
+    export_schema(&schema_for!(TournamentResponse), &out_dir);
+    export_schema(&schema_for!(TournamentEntryResponse), &out_dir);
+    export_schema(&schema_for!(StandingsResponse), &out_dir);
+    export_schema(&schema_for!(VerifyRoundResponse), &out_dir);
+    export_schema(&schema_for!(HouseChainResponse), &out_dir);
+    export_schema(&schema_for!(HouseSeedResponse), &out_dir);
 }
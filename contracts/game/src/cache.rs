@@ -0,0 +1,193 @@
+//! Storage writes held back until committed.
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::ops::Bound;
+
+use cosmwasm_std::{Order, Pair, Storage};
+
+/// Writes over `storage`, kept apart until [`Cache::commit`]. Dropping the cache
+/// instead leaves `storage` as it was, so a draw that fails halfway leaves nothing.
+pub struct Cache<'a> {
+    storage: &'a mut dyn Storage,
+    /// `None` for a removed key
+    writes: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl<'a> Cache<'a> {
+    pub fn new(storage: &'a mut dyn Storage) -> Self {
+        Cache {
+            storage,
+            writes: BTreeMap::new(),
+        }
+    }
+
+    pub fn commit(self) {
+        for (key, value) in self.writes {
+            match value {
+                Some(value) => self.storage.set(&key, &value),
+                None => self.storage.remove(&key),
+            }
+        }
+    }
+}
+
+impl Storage for Cache<'_> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.writes.get(key) {
+            Some(value) => value.clone(),
+            None => self.storage.get(key),
+        }
+    }
+
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Pair> + 'b> {
+        if let (Some(start), Some(end)) = (start, end) {
+            if start >= end {
+                return Box::new(std::iter::empty());
+            }
+        }
+        let bounds = (
+            start.map_or(Bound::Unbounded, |start| Bound::Included(start.to_vec())),
+            end.map_or(Bound::Unbounded, |end| Bound::Excluded(end.to_vec())),
+        );
+        let writes = self.writes.range(bounds);
+        let writes: Writes<'b> = match order {
+            Order::Ascending => Box::new(writes),
+            Order::Descending => Box::new(writes.rev()),
+        };
+        Box::new(Merged {
+            stored: self.storage.range(start, end, order).peekable(),
+            writes: writes.peekable(),
+            order,
+        })
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.writes.insert(key.to_vec(), None);
+    }
+}
+
+type Writes<'a> = Box<dyn Iterator<Item = (&'a Vec<u8>, &'a Option<Vec<u8>>)> + 'a>;
+
+/// Stored pairs with the writes laid over them, both in `order`.
+struct Merged<'a> {
+    stored: Peekable<Box<dyn Iterator<Item = Pair> + 'a>>,
+    writes: Peekable<Writes<'a>>,
+    order: Order,
+}
+
+impl Iterator for Merged<'_> {
+    type Item = Pair;
+
+    fn next(&mut self) -> Option<Pair> {
+        loop {
+            let next_written = match (self.stored.peek(), self.writes.peek()) {
+                (_, None) => return self.stored.next(),
+                (None, Some(_)) => Ordering::Less,
+                (Some((stored, _)), Some((written, _))) => match self.order {
+                    Order::Ascending => written.cmp(&stored),
+                    Order::Descending => stored.cmp(written),
+                },
+            };
+            if next_written == Ordering::Greater {
+                return self.stored.next();
+            }
+            if next_written == Ordering::Equal {
+                // overwritten or removed
+                self.stored.next();
+            }
+            if let Some((key, Some(value))) = self.writes.next() {
+                return Some((key.clone(), value.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    fn stored() -> MockStorage {
+        let mut storage = MockStorage::new();
+        for key in [b"a", b"c", b"e"] {
+            storage.set(key, key);
+        }
+        storage
+    }
+
+    fn keys(storage: &dyn Storage, order: Order) -> Vec<Vec<u8>> {
+        storage
+            .range(None, None, order)
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    #[test]
+    fn commit_or_drop() {
+        let mut storage = stored();
+        let mut cache = Cache::new(&mut storage);
+        cache.set(b"b", b"b");
+        cache.set(b"c", b"x");
+        cache.remove(b"e");
+        assert_eq!(Some(b"x".to_vec()), cache.get(b"c"));
+        assert_eq!(None, cache.get(b"e"));
+        drop(cache);
+        assert_eq!(Some(b"c".to_vec()), storage.get(b"c"));
+        assert_eq!(None, storage.get(b"b"));
+
+        let mut cache = Cache::new(&mut storage);
+        cache.set(b"b", b"b");
+        cache.remove(b"e");
+        cache.commit();
+        assert_eq!(
+            vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+            keys(&storage, Order::Ascending)
+        );
+    }
+
+    #[test]
+    fn range_over_writes() {
+        let mut storage = stored();
+        let mut cache = Cache::new(&mut storage);
+        cache.set(b"b", b"b");
+        cache.set(b"c", b"x");
+        cache.remove(b"e");
+        cache.set(b"f", b"f");
+
+        let ascending: Vec<Pair> = cache.range(None, None, Order::Ascending).collect();
+        assert_eq!(
+            vec![
+                (b"a".to_vec(), b"a".to_vec()),
+                (b"b".to_vec(), b"b".to_vec()),
+                (b"c".to_vec(), b"x".to_vec()),
+                (b"f".to_vec(), b"f".to_vec()),
+            ],
+            ascending
+        );
+        assert_eq!(
+            vec![b"f".to_vec(), b"c".to_vec(), b"b".to_vec(), b"a".to_vec()],
+            keys(&cache, Order::Descending)
+        );
+        let bounded: Vec<Vec<u8>> = cache
+            .range(Some(b"b"), Some(b"f"), Order::Descending)
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(vec![b"c".to_vec(), b"b".to_vec()], bounded);
+        assert_eq!(
+            0,
+            cache
+                .range(Some(b"f"), Some(b"b"), Order::Ascending)
+                .count()
+        );
+    }
+}
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 #[derive(
     Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, JsonSchema,
 )]
-pub enum BJCard {
+pub enum Rank {
     Two,
     Three,
     Four,
@@ -17,14 +17,75 @@ pub enum BJCard {
     Nine,
     Ten,
     Jack,
-    Queeen,
+    Queen,
     King,
     Ace,
 }
 
+impl Display for Rank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rank::Jack => write!(f, "J"),
+            Rank::Queen => write!(f, "Q"),
+            Rank::King => write!(f, "K"),
+            Rank::Ace => write!(f, "A"),
+            _ => write!(f, "{}", *self as u8 + 2), // Card starts from two...ten
+        }
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, JsonSchema,
+)]
+pub enum Suit {
+    Spades,
+    Hearts,
+    Diamonds,
+    Clubs,
+}
+
+impl Suit {
+    pub fn is_red(&self) -> bool {
+        matches!(self, Suit::Hearts | Suit::Diamonds)
+    }
+}
+
+/// "♠", or "s" with the alternate flag `{:#}`
+impl Display for Suit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (symbol, letter) = match self {
+            Suit::Spades => ("♠", "s"),
+            Suit::Hearts => ("♥", "h"),
+            Suit::Diamonds => ("♦", "d"),
+            Suit::Clubs => ("♣", "c"),
+        };
+        write!(f, "{}", if f.alternate() { letter } else { symbol })
+    }
+}
+
+/// One of the 52 cards of a deck. Only the rank counts for the score.
+#[derive(
+    Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, JsonSchema,
+)]
+pub struct BJCard {
+    pub rank: Rank,
+    pub suit: Suit,
+}
+
+impl BJCard {
+    pub const fn new(rank: Rank, suit: Suit) -> Self {
+        Self { rank, suit }
+    }
+}
+
+/// "A♠", or "Ah" with the alternate flag `{:#}`
 impl Display for BJCard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", (*self as u8 + 2u8) % 13) // Card starts from two...ace
+        if f.alternate() {
+            write!(f, "{}{:#}", self.rank, self.suit)
+        } else {
+            write!(f, "{}{}", self.rank, self.suit)
+        }
     }
 }
 
@@ -35,18 +96,71 @@ pub fn hand_to_string(hand: &Hand) -> String {
     str
 }
 
-pub const CARDLIST: [BJCard; 13] = [
-    BJCard::Two,
-    BJCard::Three,
-    BJCard::Four,
-    BJCard::Five,
-    BJCard::Six,
-    BJCard::Seven,
-    BJCard::Eight,
-    BJCard::Nine,
-    BJCard::Ten,
-    BJCard::Jack,
-    BJCard::Queeen,
-    BJCard::King,
-    BJCard::Ace,
+pub const RANKS: [Rank; 13] = [
+    Rank::Two,
+    Rank::Three,
+    Rank::Four,
+    Rank::Five,
+    Rank::Six,
+    Rank::Seven,
+    Rank::Eight,
+    Rank::Nine,
+    Rank::Ten,
+    Rank::Jack,
+    Rank::Queen,
+    Rank::King,
+    Rank::Ace,
 ];
+
+pub const SUITS: [Suit; 4] = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
+
+/// A full deck, suit by suit.
+pub const CARDLIST: [BJCard; 52] = {
+    let mut cards = [BJCard::new(Rank::Two, Suit::Spades); 52];
+    let mut i = 0;
+    while i < cards.len() {
+        cards[i] = BJCard::new(RANKS[i % RANKS.len()], SUITS[i / RANKS.len()]);
+        i += 1;
+    }
+    cards
+};
+
+/// Spades of the given ranks, for fixtures where the suit does not matter.
+#[cfg(test)]
+pub(crate) fn hand(ranks: &[Rank]) -> Hand {
+    ranks
+        .iter()
+        .map(|r| BJCard::new(*r, Suit::Spades))
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) fn ranks(hand: &[BJCard]) -> Vec<Rank> {
+    hand.iter().map(|c| c.rank).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!("2♠", BJCard::new(Rank::Two, Suit::Spades).to_string());
+        assert_eq!("10♥", BJCard::new(Rank::Ten, Suit::Hearts).to_string());
+        assert_eq!("J♦", BJCard::new(Rank::Jack, Suit::Diamonds).to_string());
+        assert_eq!("Q♣", BJCard::new(Rank::Queen, Suit::Clubs).to_string());
+        assert_eq!("K♠", BJCard::new(Rank::King, Suit::Spades).to_string());
+        assert_eq!("A♠", BJCard::new(Rank::Ace, Suit::Spades).to_string());
+
+        assert_eq!("10h", format!("{:#}", BJCard::new(Rank::Ten, Suit::Hearts)));
+        assert_eq!("Ac", format!("{:#}", BJCard::new(Rank::Ace, Suit::Clubs)));
+    }
+
+    #[test]
+    fn full_deck() {
+        let mut deck = CARDLIST.to_vec();
+        deck.sort();
+        deck.dedup();
+        assert_eq!(52, deck.len());
+    }
+}
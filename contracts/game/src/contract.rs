@@ -1,29 +1,50 @@
+use bj_common::parse_version;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::CosmosMsg;
 use cosmwasm_std::WasmMsg;
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-    Storage, Uint128,
+    from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order,
+    Response, StdError, StdResult, Storage, Uint128,
 };
-use cw2::set_contract_version;
+use cosmwasm_std::{BankMsg, Coin, CosmosMsg};
+use cw2::{get_contract_version, set_contract_version};
 use cw20::Cw20ExecuteMsg;
 use cw20::Cw20ReceiveMsg;
+use cw_storage_plus::{Bound, PrimaryKey, U128Key, U64Key};
 
 use crate::card::hand_to_string;
 use crate::error::ContractError;
-use crate::game::dealer_action;
+use crate::game::{GameResult, Judge, PairResult, PokerJudge};
 use crate::msg::{
-    ActionCommand, Cw20HookMsg, DepositResponse, ExecuteMsg, GameStateResponce, InstantiateMsg,
-    QueryMsg,
+    ActionCommand, BankrollResponse, ConfigResponse, Cw20HookMsg, DepositResponse, ExecuteMsg,
+    GameStateResponce, HistoryResponse, InstantiateMsg, LeaderboardEntry, LeaderboardResponse,
+    LiquidityResponse, MigrateMsg, QueryMsg, RankBy, ShoeResponse, StateResponse, StatsResponse,
+    TokenResponse, TokensResponse, UpdateConfigMsg,
 };
-use crate::state::{Config, GameState, State, Vault, CONFIG, GAMESTATE, STATE, VAULT};
-use crate::{game, random};
+use crate::play::{self, Closing, Rules, Turn};
+use crate::shoe::Shoe;
+use crate::state::{
+    player_stats, split_stats_key, stats_prefix, AssetInfo, Bankroll, BetLimits, Config, GameState,
+    HandResult, HouseSeed, Move, PlayerHand, PlayerSeed, Round, RoundEntropy, SeedRequest, State,
+    TimeoutAction, TokenConfig, Vault, BANKROLL, CONFIG, GAMESTATE, GAME_ENTROPY, HISTORY,
+    LIQUIDITY, LIQUIDITY_WIPEOUTS, ROUND_COUNT, ROUND_ENTROPY, SHOE, STATE, STATS, TOKENS, VAULT,
+};
+use crate::{game, house, migration, random, table, tournament, verify};
 
 // version info for migration info
-const CONTRACT_NAME: &str = "crates.io:project-name";
+const CONTRACT_NAME: &str = "crates.io:bj-game";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// a pair can be split until the player holds this many hands
+const MAX_HANDS: usize = 4;
+
+pub(crate) const DEFAULT_DECKS: u8 = 6;
+// percent of the shoe dealt before reshuffling
+pub(crate) const DEFAULT_PENETRATION: u64 = 75;
+// one day
+pub(crate) const DEFAULT_TIMEOUT: u64 = 86_400;
+const MAX_DECKS: u8 = 8;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -33,22 +54,63 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     let state = State {
         owner: info.sender.clone(),
+        pending_owner: None,
+        paused: false,
     };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
 
-    let token_address = deps.api.addr_validate(&msg.cw20_address)?;
     let config = Config {
-        token_address: token_address.clone(),
+        insurance: msg.insurance.unwrap_or(false),
+        payout_table: msg.payout_table.unwrap_or_default(),
+        decks: msg.decks.unwrap_or(DEFAULT_DECKS),
+        penetration: msg
+            .penetration
+            .unwrap_or_else(|| Decimal::percent(DEFAULT_PENETRATION)),
+        dealer_hits_soft_17: msg.dealer_hits_soft_17.unwrap_or(false),
+        perfect_pairs: msg.perfect_pairs.unwrap_or_default(),
+        twenty_one_plus_three: msg.twenty_one_plus_three.unwrap_or_default(),
+        timeout: msg.timeout.unwrap_or(DEFAULT_TIMEOUT),
+        timeout_action: msg.timeout_action.unwrap_or_default(),
     };
+    validate_config(&config)?;
     CONFIG.save(deps.storage, &config)?;
 
+    let token_address = deps.api.addr_validate(&msg.cw20_address)?;
+    add_token(
+        deps.storage,
+        &AssetInfo::cw20(&token_address),
+        msg.bet_limits.unwrap_or_default(),
+    )?;
+
     Ok(Response::new()
         .add_attribute("method", "instantiate")
         .add_attribute("owner", info.sender)
         .add_attribute("cw20_address", token_address))
 }
 
+fn validate_config(config: &Config) -> Result<(), ContractError> {
+    if !config.payout_table.is_valid()
+        || !config.perfect_pairs.is_valid()
+        || !config.twenty_one_plus_three.is_valid()
+    {
+        return Err(ContractError::InvalidPayoutTable {});
+    }
+
+    if !(1..=MAX_DECKS).contains(&config.decks)
+        || config.penetration.is_zero()
+        || config.penetration > Decimal::one()
+    {
+        return Err(ContractError::InvalidShoe {});
+    }
+
+    if config.timeout == 0 {
+        return Err(ContractError::InvalidTimeout {});
+    }
+
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -57,57 +119,326 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Receive(msg) => receive_cw20(deps, info, msg),
-        ExecuteMsg::Bet { amount } => try_bet(deps, _env, info, amount),
-        ExecuteMsg::Action { action } => try_action(deps, _env, info, action),
-        ExecuteMsg::Withdraw { amount } => try_withdraw(deps, info, amount),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, _env, info, msg),
+        ExecuteMsg::Bet {
+            token,
+            amount,
+            seed_commitment,
+            perfect_pairs,
+            twenty_one_plus_three,
+        } => try_bet(
+            deps,
+            _env,
+            info,
+            token,
+            amount,
+            seed_commitment,
+            perfect_pairs.unwrap_or_default(),
+            twenty_one_plus_three.unwrap_or_default(),
+            false,
+        ),
+        ExecuteMsg::Action {
+            token,
+            action,
+            seed,
+        } => try_action(deps, _env, info, token, action, seed),
+        ExecuteMsg::ForceSettle { address, token } => try_force_settle(deps, _env, address, token),
+        ExecuteMsg::Deposit {} => try_deposit(deps, info),
+        ExecuteMsg::Withdraw { amount, asset } => try_withdraw(deps, info, amount, asset),
+        ExecuteMsg::ProvideLiquidity {} => try_provide_liquidity(deps, info),
+        ExecuteMsg::WithdrawLiquidity { token, shares } => {
+            try_withdraw_liquidity(deps, info, token, shares)
+        }
+        ExecuteMsg::UpdateConfig(msg) => try_update_config(deps, info, msg),
+        ExecuteMsg::AddToken { token, bet_limits } => {
+            try_add_token(deps, info, token, bet_limits.unwrap_or_default())
+        }
+        ExecuteMsg::RemoveToken { token } => try_remove_token(deps, info, token),
+        ExecuteMsg::OpenTable {
+            token,
+            betting_window,
+            turn_timeout,
+        } => table::try_open_table(deps, _env, info, token, betting_window, turn_timeout),
+        ExecuteMsg::JoinTable { table_id } => table::try_join_table(deps, info, table_id),
+        ExecuteMsg::LeaveTable { table_id } => table::try_leave_table(deps, info, table_id),
+        ExecuteMsg::TableBet {
+            table_id,
+            amount,
+            seed_commitment,
+        } => table::try_table_bet(deps, _env, info, table_id, amount, seed_commitment),
+        ExecuteMsg::DealTable { table_id } => table::try_deal_table(deps, _env, table_id),
+        ExecuteMsg::TableAction {
+            table_id,
+            action,
+            seed,
+        } => table::try_table_action(deps, _env, info, table_id, action, seed),
+        ExecuteMsg::ForceStand { table_id } => table::try_force_stand(deps, _env, table_id),
+        ExecuteMsg::CreateTournament {
+            token,
+            buy_in,
+            starting_chips,
+            starts_at,
+            ends_at,
+            rounds,
+            payouts,
+        } => tournament::try_create_tournament(
+            deps,
+            _env,
+            info,
+            token,
+            buy_in,
+            starting_chips,
+            starts_at,
+            ends_at,
+            rounds,
+            payouts,
+        ),
+        ExecuteMsg::RegisterTournament { tournament_id } => {
+            tournament::try_register(deps, _env, info, tournament_id)
+        }
+        ExecuteMsg::TournamentBet {
+            tournament_id,
+            amount,
+            seed_commitment,
+        } => {
+            tournament::try_tournament_bet(deps, _env, info, tournament_id, amount, seed_commitment)
+        }
+        ExecuteMsg::TournamentAction {
+            tournament_id,
+            action,
+            seed,
+        } => tournament::try_tournament_action(deps, _env, info, tournament_id, action, seed),
+        ExecuteMsg::SettleTournament { tournament_id } => {
+            tournament::try_settle_tournament(deps, _env, tournament_id)
+        }
+        ExecuteMsg::ProposeNewOwner { owner } => try_propose_new_owner(deps, info, owner),
+        ExecuteMsg::AcceptOwnership {} => try_accept_ownership(deps, info),
+        ExecuteMsg::CommitHouseChain { anchor } => {
+            house::try_commit_house_chain(deps, info, anchor)
+        }
+        ExecuteMsg::RevealHouseSeed { index, seed } => {
+            house::try_reveal_house_seed(deps, _env, index, seed)
+        }
+        ExecuteMsg::Pause {} => try_set_paused(deps, info, true),
+        ExecuteMsg::Unpause {} => try_set_paused(deps, info, false),
     }
 }
 
 pub fn receive_cw20(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
-    let contract_address = info.sender;
+    // validate cw20 contract
+    let token = AssetInfo::cw20(&info.sender);
+    if !TOKENS.has(deps.storage, &token) {
+        return Err(ContractError::Unauthorized {});
+    }
+
     match from_binary(&cw20_msg.msg) {
         Ok(Cw20HookMsg::Deposit {}) => {
-            // validate cw20 contract
-            let config: Config = CONFIG.load(deps.storage)?;
-            if config.token_address != contract_address {
-                return Err(ContractError::Unauthorized {});
-            }
-
-            let transfer_amount = cw20_msg.amount;
-            let new_vault = VAULT.update(
+            let new_vault = deposit(
                 deps.storage,
                 &Addr::unchecked(cw20_msg.sender),
-                |d: Option<Vault>| -> StdResult<Vault> {
-                    match d {
-                        Some(vault) => Ok(Vault {
-                            balance: vault.balance.saturating_add(transfer_amount),
-                        }),
-                        None => Ok(Vault {
-                            balance: transfer_amount,
-                        }),
-                    }
-                },
+                &token,
+                cw20_msg.amount,
             )?;
 
             Ok(Response::new()
                 .add_attribute("action", "deposit")
                 .add_attribute("amount", new_vault.balance))
         }
+        Ok(Cw20HookMsg::Bet {
+            amount,
+            seed_commitment,
+            perfect_pairs,
+            twenty_one_plus_three,
+            auto_withdraw,
+        }) => {
+            let player = deps.api.addr_validate(&cw20_msg.sender)?;
+            deposit(deps.storage, &player, &token, cw20_msg.amount)?;
+
+            let info = MessageInfo {
+                sender: player,
+                funds: vec![],
+            };
+            try_bet(
+                deps,
+                env,
+                info,
+                token,
+                amount.unwrap_or(cw20_msg.amount),
+                seed_commitment,
+                perfect_pairs.unwrap_or_default(),
+                twenty_one_plus_three.unwrap_or_default(),
+                auto_withdraw.unwrap_or(false),
+            )
+        }
+        Ok(Cw20HookMsg::ProvideLiquidity {}) => {
+            let provider = deps.api.addr_validate(&cw20_msg.sender)?;
+            provide_liquidity(deps.storage, &provider, &token, cw20_msg.amount)
+        }
         Err(err) => Err(ContractError::Std(err)),
     }
 }
 
+/// Mint LP shares for `amount` added to the bankroll of `token`.
+fn provide_liquidity(
+    storage: &mut dyn Storage,
+    provider: &Addr,
+    token: &AssetInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    // a balance without shares, kept by the house after every provider left,
+    // belongs to the owner rather than to the next provider
+    let mut bankroll = BANKROLL.load(storage, token)?;
+    if bankroll.total_shares.is_zero() && !bankroll.balance.is_zero() {
+        let owner = STATE.load(storage)?.owner;
+        add_shares(storage, &owner, token, &bankroll, bankroll.balance)?;
+        bankroll.total_shares = bankroll.balance;
+    }
+    // shares left on a bankroll wiped out are worth nothing, the next provider starts anew
+    if bankroll.balance.is_zero() && !bankroll.total_shares.is_zero() {
+        bankroll.total_shares = Uint128::zero();
+        bankroll.wipeouts += 1;
+    }
+
+    // shares are minted at the current value of the bankroll
+    let shares = if bankroll.total_shares.is_zero() {
+        amount
+    } else {
+        amount.multiply_ratio(bankroll.total_shares, bankroll.balance)
+    };
+    if shares.is_zero() {
+        return Err(ContractError::LiquidityTooSmall {});
+    }
+    bankroll.balance += amount;
+    bankroll.total_shares += shares;
+    BANKROLL.save(storage, token, &bankroll)?;
+
+    let position = add_shares(storage, provider, token, &bankroll, shares)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "provide_liquidity")
+        .add_attribute("amount", amount)
+        .add_attribute("shares", shares)
+        .add_attribute("position", position))
+}
+
+/// LP shares of `provider`, none of them left after the bankroll was wiped out.
+fn shares_of(
+    storage: &dyn Storage,
+    provider: &Addr,
+    token: &AssetInfo,
+    bankroll: &Bankroll,
+) -> StdResult<Uint128> {
+    let wipeouts = LIQUIDITY_WIPEOUTS
+        .may_load(storage, (provider, token))?
+        .unwrap_or_default();
+    if wipeouts != bankroll.wipeouts {
+        return Ok(Uint128::zero());
+    }
+    Ok(LIQUIDITY
+        .may_load(storage, (provider, token))?
+        .unwrap_or_default())
+}
+
+/// Add `shares` to the position of `provider` and return it.
+fn add_shares(
+    storage: &mut dyn Storage,
+    provider: &Addr,
+    token: &AssetInfo,
+    bankroll: &Bankroll,
+    shares: Uint128,
+) -> StdResult<Uint128> {
+    let position = shares_of(storage, provider, token, bankroll)? + shares;
+    LIQUIDITY.save(storage, (provider, token), &position)?;
+    LIQUIDITY_WIPEOUTS.save(storage, (provider, token), &bankroll.wipeouts)?;
+    Ok(position)
+}
+
+/// Provide the bank coin sent to the bankroll of its denom.
+fn try_provide_liquidity(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let coin = match &info.funds[..] {
+        [coin] => coin,
+        [] => return Err(ContractError::NoFunds {}),
+        _ => return Err(ContractError::MultipleDenoms {}),
+    };
+    let token = AssetInfo::native(&coin.denom);
+    if !TOKENS.has(deps.storage, &token) {
+        return Err(ContractError::UnsupportedDenom {
+            denom: coin.denom.clone(),
+        });
+    }
+
+    provide_liquidity(deps.storage, &info.sender, &token, coin.amount)
+}
+
+fn deposit(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    asset: &AssetInfo,
+    amount: Uint128,
+) -> StdResult<Vault> {
+    VAULT.update(
+        storage,
+        (address, asset),
+        |d: Option<Vault>| -> StdResult<Vault> {
+            match d {
+                Some(vault) => Ok(Vault {
+                    balance: vault.balance.saturating_add(amount),
+                }),
+                None => Ok(Vault { balance: amount }),
+            }
+        },
+    )
+}
+
+/// Deposit bank coins, each denom into its own balance.
+fn try_deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    if info.funds.is_empty() {
+        return Err(ContractError::NoFunds {});
+    }
+
+    let mut res = Response::new().add_attribute("action", "deposit");
+    for coin in info.funds {
+        let asset = AssetInfo::native(&coin.denom);
+        if !TOKENS.has(deps.storage, &asset) {
+            return Err(ContractError::UnsupportedDenom { denom: coin.denom });
+        }
+        let new_vault = deposit(deps.storage, &info.sender, &asset, coin.amount)?;
+        res = res
+            .add_attribute("denom", coin.denom)
+            .add_attribute("amount", new_vault.balance);
+    }
+
+    Ok(res)
+}
+
 fn try_withdraw(
     deps: DepsMut,
     info: MessageInfo,
     amount: Uint128,
+    asset: AssetInfo,
 ) -> Result<Response, ContractError> {
-    VAULT.update(deps.storage, &info.sender, |vault| match vault {
+    let msg = withdraw(deps.storage, &info.sender, &asset, amount)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "withdraw")
+        .add_attribute("withdraw_amount", amount)
+        .add_attribute("asset", asset.to_string())
+        .add_message(msg))
+}
+
+/// Take `amount` out of the vault and transfer it to its owner.
+fn withdraw(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    asset: &AssetInfo,
+    amount: Uint128,
+) -> Result<CosmosMsg, ContractError> {
+    VAULT.update(storage, (address, asset), |vault| match vault {
         Some(mut v) => {
             if amount > v.balance {
                 return Err(ContractError::InsufficientBalance { balance: v.balance });
@@ -118,212 +449,1089 @@ fn try_withdraw(
         None => Err(ContractError::NoSuchAccountExists {}),
     })?;
 
-    let token_address = CONFIG.load(deps.storage)?.token_address;
+    Ok(transfer(asset, address, amount)?)
+}
 
-    let msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: token_address.to_string(),
-        msg: to_binary(&Cw20ExecuteMsg::Transfer {
-            recipient: info.sender.to_string(),
-            amount,
-        })?,
-        funds: vec![],
-    });
+/// Send `amount` of `asset` from the contract to `recipient`.
+fn transfer(asset: &AssetInfo, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(match asset {
+        AssetInfo::Token { contract_addr } => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }),
+        AssetInfo::NativeToken { denom } => CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom: denom.clone(),
+                amount,
+            }],
+        }),
+    })
+}
+
+/// Burn shares for their part of the bankroll.
+/// Reservations for games in progress can not be withdrawn.
+fn try_withdraw_liquidity(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: AssetInfo,
+    shares: Uint128,
+) -> Result<Response, ContractError> {
+    let mut bankroll = BANKROLL.load(deps.storage, &token)?;
+    let position = shares_of(deps.storage, &info.sender, &token, &bankroll)?;
+    if shares.is_zero() || shares > position {
+        return Err(ContractError::InsufficientShares { shares: position });
+    }
+
+    let amount = bankroll
+        .balance
+        .multiply_ratio(shares, bankroll.total_shares);
+    if amount > bankroll.available() {
+        return Err(ContractError::LiquidityReserved {
+            available: bankroll.available(),
+        });
+    }
+    bankroll.balance -= amount;
+    bankroll.total_shares -= shares;
+    BANKROLL.save(deps.storage, &token, &bankroll)?;
+    LIQUIDITY.save(deps.storage, (&info.sender, &token), &(position - shares))?;
+
+    let msg = transfer(&token, &info.sender, amount)?;
 
     Ok(Response::new()
-        .add_attribute("action", "withdraw")
+        .add_attribute("action", "withdraw_liquidity")
+        .add_attribute("shares", shares)
         .add_attribute("withdraw_amount", amount)
         .add_message(msg))
 }
 
+pub(crate) fn only_owner(
+    storage: &dyn Storage,
+    info: &MessageInfo,
+) -> Result<State, ContractError> {
+    let state = STATE.load(storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(state)
+}
+
+fn try_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: UpdateConfigMsg,
+) -> Result<Response, ContractError> {
+    only_owner(deps.storage, &info)?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    if let Some(insurance) = msg.insurance {
+        config.insurance = insurance;
+    }
+    if let Some(payout_table) = msg.payout_table {
+        config.payout_table = payout_table;
+    }
+    if let Some(decks) = msg.decks {
+        config.decks = decks;
+    }
+    if let Some(penetration) = msg.penetration {
+        config.penetration = penetration;
+    }
+    if let Some(dealer_hits_soft_17) = msg.dealer_hits_soft_17 {
+        config.dealer_hits_soft_17 = dealer_hits_soft_17;
+    }
+    if let Some(perfect_pairs) = msg.perfect_pairs {
+        config.perfect_pairs = perfect_pairs;
+    }
+    if let Some(twenty_one_plus_three) = msg.twenty_one_plus_three {
+        config.twenty_one_plus_three = twenty_one_plus_three;
+    }
+    if let Some(timeout) = msg.timeout {
+        config.timeout = timeout;
+    }
+    if let Some(timeout_action) = msg.timeout_action {
+        config.timeout_action = timeout_action;
+    }
+    validate_config(&config)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+/// Whitelist `token` with `bet_limits`, opening its bankroll the first time.
+fn add_token(
+    storage: &mut dyn Storage,
+    token: &AssetInfo,
+    bet_limits: BetLimits,
+) -> Result<(), ContractError> {
+    if !bet_limits.is_valid() {
+        return Err(ContractError::InvalidBetLimits {});
+    }
+    TOKENS.save(storage, token, &TokenConfig { bet_limits })?;
+    if !BANKROLL.has(storage, token) {
+        BANKROLL.save(storage, token, &Bankroll::default())?;
+    }
+    Ok(())
+}
+
+fn try_add_token(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: AssetInfo,
+    bet_limits: BetLimits,
+) -> Result<Response, ContractError> {
+    only_owner(deps.storage, &info)?;
+
+    if let AssetInfo::Token { contract_addr } = &token {
+        deps.api.addr_validate(contract_addr.as_str())?;
+    }
+    add_token(deps.storage, &token, bet_limits)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_token")
+        .add_attribute("token", token.to_string()))
+}
+
+fn try_remove_token(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: AssetInfo,
+) -> Result<Response, ContractError> {
+    only_owner(deps.storage, &info)?;
+
+    whitelisted(deps.storage, &token)?;
+    TOKENS.remove(deps.storage, &token);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_token")
+        .add_attribute("token", token.to_string()))
+}
+
+/// Limits of a whitelisted token, which new stakes need.
+pub(crate) fn whitelisted(
+    storage: &dyn Storage,
+    token: &AssetInfo,
+) -> Result<TokenConfig, ContractError> {
+    TOKENS
+        .may_load(storage, token)?
+        .ok_or_else(|| ContractError::TokenNotWhitelisted {
+            token: token.to_string(),
+        })
+}
+
+fn try_propose_new_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: String,
+) -> Result<Response, ContractError> {
+    let mut state = only_owner(deps.storage, &info)?;
+
+    let pending_owner = deps.api.addr_validate(&owner)?;
+    state.pending_owner = Some(pending_owner.clone());
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_new_owner")
+        .add_attribute("pending_owner", pending_owner))
+}
+
+fn try_accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if state.pending_owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    state.owner = info.sender.clone();
+    state.pending_owner = None;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("owner", info.sender))
+}
+
+fn try_set_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let mut state = only_owner(deps.storage, &info)?;
+
+    state.paused = paused;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attribute("action", if paused { "pause" } else { "unpause" }))
+}
+
+/// Table limits apply to every stake on a hand, top-ups included.
+pub(crate) fn check_bet_limits(
+    storage: &dyn Storage,
+    token: &AssetInfo,
+    limits: &BetLimits,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::BetAmountZero {});
+    }
+
+    if amount < limits.min_bet {
+        return Err(ContractError::BetTooSmall {
+            min_bet: limits.min_bet,
+        });
+    }
+
+    if let Some(max_bet) = limits.max_bet {
+        if amount > max_bet {
+            return Err(ContractError::BetTooLarge { max_bet });
+        }
+    }
+
+    if let Some(ratio) = limits.max_bankroll_ratio {
+        let max_bet = BANKROLL.load(storage, token)?.available() * ratio;
+        if amount > max_bet {
+            return Err(ContractError::BetExceedsBankrollLimit { max_bet });
+        }
+    }
+
+    Ok(())
+}
+
 /// User bet against the dealer.
 /// fail if bet amount is bigger than deposit.
 ///
 /// The game starts here.
+/// The cards are dealt once the house reveals the seed the bet waits on,
+/// mixed with the commitment until the player reveals the seed.
+/// Side bets are paid from the first deal.
+#[allow(clippy::too_many_arguments)]
 pub fn try_bet(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    token: AssetInfo,
     amount: Uint128,
+    seed_commitment: Binary,
+    perfect_pairs: Uint128,
+    twenty_one_plus_three: Uint128,
+    auto_withdraw: bool,
 ) -> Result<Response, ContractError> {
-    if amount < Uint128::new(0) {
-        return Err(ContractError::BetAmountZero {});
+    if STATE.load(deps.storage)?.paused {
+        return Err(ContractError::Paused {});
     }
 
-    let balance_after = exec_bet(deps.storage, &info, amount)?;
-
-    let deal = game::first_deal(&mut random::gen_rng(env.block.time));
-    let hand_dealer: String = deal.0.iter().map(|c| c.to_string() + " ").collect();
-    let hand_player: String = deal.1.iter().map(|c| c.to_string() + " ").collect();
+    let config = CONFIG.load(deps.storage)?;
+    let limits = whitelisted(deps.storage, &token)?.bet_limits;
+    check_bet_limits(deps.storage, &token, &limits, amount)?;
 
-    let state_after = GAMESTATE.update(deps.storage, &info.sender, |state| {
-        let new_game = GameState {
-            ingame: true,
-            total_bet_amount: amount,
-            dealer_hand: deal.0,
-            player_hand: deal.1,
-        };
-        match state {
-            Some(v) => {
-                if v.ingame {
-                    return Err(ContractError::BetAfterStart {});
-                }
+    if seed_commitment.len() != random::COMMITMENT_LENGTH {
+        return Err(ContractError::InvalidCommitment {});
+    }
 
-                Ok(new_game)
-            }
-            None => Ok(new_game),
+    if let Some(state) = GAMESTATE.may_load(deps.storage, (&info.sender, &token))? {
+        if state.ingame {
+            return Err(ContractError::BetAfterStart {});
         }
-    })?;
+    }
+
+    let stake = amount
+        .checked_add(perfect_pairs)
+        .and_then(|a| a.checked_add(twenty_one_plus_three))
+        .map_err(StdError::from)?;
+
+    // the bankroll must cover a doubled down win and the best paying side bets
+    let liability = config.payout_table.max_payout(amount)
+        + config.perfect_pairs.max_payout(perfect_pairs)
+        + config
+            .twenty_one_plus_three
+            .max_payout(twenty_one_plus_three);
+    reserve(deps.storage, &token, stake, liability)?;
+    let balance_after = exec_bet(deps.storage, &info, &token, stake)?;
+
+    // reshuffle between rounds once the cut card is reached
+    let mut shoe = match SHOE.may_load(deps.storage, (&info.sender, &token))? {
+        Some(shoe) if shoe.decks == config.decks && !shoe.needs_shuffle(config.penetration) => shoe,
+        _ => Shoe::new(config.decks),
+    };
+    shoe.discard();
+
+    let request = SeedRequest::Game {
+        player: info.sender.clone(),
+        token: token.clone(),
+    };
+    let pending = house::request_seed(deps.storage, &env, Move::Deal, &request)?;
+    let entropy = RoundEntropy {
+        shoe: shoe.clone(),
+        players: vec![PlayerSeed {
+            player: info.sender.clone(),
+            seed_commitment: seed_commitment.clone(),
+            seed: None,
+        }],
+        table_id: None,
+        insurance: config.insurance,
+        dealer_hits_soft_17: config.dealer_hits_soft_17,
+        house_seeds: vec![],
+    };
+    GAME_ENTROPY.save(deps.storage, (&info.sender, &token), &entropy)?;
+
+    let game = GameState {
+        ingame: true,
+        total_bet_amount: amount,
+        player_hands: vec![PlayerHand {
+            cards: vec![],
+            bet_amount: amount,
+        }],
+        perfect_pairs_amount: perfect_pairs,
+        twenty_one_plus_three_amount: twenty_one_plus_three,
+        liability,
+        seed_commitment,
+        pending: Some(pending),
+        started_height: env.block.height,
+        started_at: env.block.time,
+        turn_started: env.block.time,
+        auto_withdraw,
+        ..GameState::default()
+    };
+    GAMESTATE.save(deps.storage, (&info.sender, &token), &game)?;
+    SHOE.save(deps.storage, (&info.sender, &token), &shoe)?;
 
     Ok(Response::new()
         .add_attribute("action", "bet")
-        .add_attribute("bet_amount", state_after.total_bet_amount)
-        .add_attribute("balance_after", balance_after.balance)
-        .add_attribute("dealer_cards", hand_dealer)
-        .add_attribute("player_cards", hand_player))
+        .add_attribute("bet_amount", game.total_bet_amount)
+        .add_attribute("balance_after", balance_after.balance))
 }
 
+/// The seed committed at bet must be revealed with the first action.
+/// The action is checked and staked here, its cards are drawn once the house reveals
+/// the seed it waits on.
 pub fn try_action(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    token: AssetInfo,
     action: ActionCommand,
+    seed: Option<Binary>,
 ) -> Result<Response, ContractError> {
     let mut game = GAMESTATE
-        .load(deps.storage, &info.sender)
+        .load(deps.storage, (&info.sender, &token))
         .map_err(|_| ContractError::NoSuchAccountExists {})?;
 
     if !game.ingame {
         return Err(ContractError::ActionBeforeBet {});
     }
+    if game.pending.is_some() {
+        return Err(ContractError::SeedPending {});
+    }
 
-    match action {
-        ActionCommand::Hit => {
-            let draw = game::draw_one(&mut random::gen_rng(env.block.time));
-            game.player_hand.push(draw);
+    let player_seed = reveal_seed(game.player_seed.clone(), seed, &game.seed_commitment)?;
+    game.player_seed = Some(player_seed);
 
-            // check busted or not
-            if let Judge::PlayerBusted(_) = game::judge(&[], &game.player_hand) {
-                // continue
-            } else {
-                GAMESTATE.save(deps.storage, &info.sender, &game)?;
+    let config = CONFIG.load(deps.storage)?;
+    let rules = Rules::of(&config);
+    let action_name = match action {
+        ActionCommand::Stand => "stand",
+        ActionCommand::Hit => "hit",
+        ActionCommand::DoubleDown { amount: _ } => "doubledown",
+        ActionCommand::Split { amount: _ } => "split",
+        ActionCommand::Insurance { amount: _ } => "insurance",
+        ActionCommand::Surrender => "surrender",
+    };
 
-                return Ok(Response::new()
-                    .add_attribute("action", "hit")
-                    .add_attribute("dealer_cards", hand_to_string(&game.dealer_hand))
-                    .add_attribute("player_cards", hand_to_string(&game.player_hand))
-                    .add_attribute("draw", draw.to_string()));
-            }
-        }
+    let active = game.active_hand as usize;
+    let hands = game.player_hands.len();
+    let hand = game
+        .player_hands
+        .get(active)
+        .ok_or(ContractError::InvalidState {})?;
+
+    let mut res = Response::new().add_attribute("action", action_name);
+    match action {
         ActionCommand::DoubleDown { amount } => {
-            // raise, draw one, then close game
-            if amount != game.total_bet_amount {
+            if amount != hand.bet_amount {
                 return Err(ContractError::WrongDoublDownAmount {
-                    amount: game.total_bet_amount,
+                    amount: hand.bet_amount,
                 });
             }
 
-            if game.player_hand.len() != 2 {
+            if hand.cards.len() != 2 {
                 return Err(ContractError::DoubleDownNotAllowed {});
             }
 
-            let _ = exec_bet(deps.storage, &info, amount)?;
+            let limits = whitelisted(deps.storage, &token)?.bet_limits;
+            check_bet_limits(deps.storage, &token, &limits, amount)?;
 
-            game.total_bet_amount += amount;
-            game.player_hand
-                .push(game::draw_one(&mut random::gen_rng(env.block.time)));
+            // covered by the reservation made at bet
+            let _ = exec_bet(deps.storage, &info, &token, amount)?;
+            reserve(deps.storage, &token, amount, Uint128::new(0))?;
         }
-        ActionCommand::Stand => {
-            // do nothing, close game
+        ActionCommand::Split { amount } => {
+            if amount != hand.bet_amount {
+                return Err(ContractError::WrongSplitAmount {
+                    amount: hand.bet_amount,
+                });
+            }
+
+            if !game::can_split(&hand.cards) || hands >= MAX_HANDS {
+                return Err(ContractError::SplitNotAllowed {});
+            }
+
+            let limits = whitelisted(deps.storage, &token)?.bet_limits;
+            check_bet_limits(deps.storage, &token, &limits, amount)?;
+
+            let _ = exec_bet(deps.storage, &info, &token, amount)?;
+            let liability = config.payout_table.max_payout(amount);
+            reserve(deps.storage, &token, amount, liability)?;
+            game.liability += liability;
         }
-    }
+        ActionCommand::Insurance { amount } => {
+            if !play::insurance_offered(&game, &rules) {
+                return Err(ContractError::InsuranceNotAllowed {});
+            }
 
-    use game::{GameResult, Judge};
+            let max = hand.bet_amount.multiply_ratio(1u128, 2u128);
+            if amount.is_zero() || amount > max {
+                return Err(ContractError::WrongInsuranceAmount { max });
+            }
 
-    // dealer draw if player is not busted
-    let new_dealer_hand = if let Judge::PlayerBusted(_) = game::judge(&[], &game.player_hand) {
-        game.dealer_hand
-    } else {
-        dealer_action(&game.dealer_hand, &mut random::gen_rng(env.block.time))
-    };
+            let balance_after = exec_bet(deps.storage, &info, &token, amount)?;
+            let liability = amount * config.payout_table.insurance;
+            reserve(deps.storage, &token, amount, liability)?;
+            game.liability += liability;
 
-    let judge = game::judge(&new_dealer_hand, &game.player_hand);
+            res = res
+                .add_attribute("insurance_amount", amount)
+                .add_attribute("balance_after", balance_after.balance);
+        }
+        ActionCommand::Surrender => {
+            // close game without dealer action
+            if hands != 1 || hand.cards.len() != 2 {
+                return Err(ContractError::SurrenderNotAllowed {});
+            }
+        }
+        ActionCommand::Hit | ActionCommand::Stand => {}
+    }
 
-    let result = match judge {
-        Judge::DealerBusted(_) => GameResult::Win,
-        Judge::PlayerBusted(_) => GameResult::Loose,
-        Judge::DealerWin(_, _) => GameResult::Loose,
-        Judge::PlayerWin(_, _) => GameResult::Win,
-        Judge::PlayerBJWin(_, _) => GameResult::Win,
-        Judge::Draw(_, _) => GameResult::Draw,
+    let request = SeedRequest::Game {
+        player: info.sender.clone(),
+        token: token.clone(),
     };
+    game.pending = Some(house::request_seed(
+        deps.storage,
+        &env,
+        Move::Action(action),
+        &request,
+    )?);
+    game.turn_started = env.block.time;
+    GAMESTATE.save(deps.storage, (&info.sender, &token), &game)?;
 
-    // change balance
-    let balance_change = match result {
-        GameResult::Win => game.total_bet_amount.saturating_mul(Uint128::new(2)),
-        GameResult::Loose => Uint128::new(0),
-        GameResult::Draw => game.total_bet_amount,
-    };
+    Ok(res)
+}
 
-    VAULT.update(
-        deps.storage,
-        &info.sender,
-        |vault: Option<Vault>| -> StdResult<Vault> {
-            let mut vault = vault.unwrap();
-            vault.balance = vault.balance.saturating_add(balance_change);
-            Ok(vault)
+/// Draw the move the game waits on from the house seed just revealed.
+pub(crate) fn draw_game(
+    storage: &mut dyn Storage,
+    env: &Env,
+    player: &Addr,
+    token: &AssetInfo,
+    house_seed: &Binary,
+) -> Result<Response, ContractError> {
+    let mut game = GAMESTATE.load(storage, (player, token))?;
+    let pending = game.pending.take().ok_or(ContractError::InvalidState {})?;
+    push_house_seed(
+        storage,
+        player,
+        token,
+        HouseSeed {
+            made_for: pending.made_for.clone(),
+            index: pending.index,
+            seed: house_seed.clone(),
+            made_at: pending.made_at,
         },
     )?;
+    game.turn_started = env.block.time;
 
-    // update state
-    game.ingame = false;
-    game.dealer_hand = new_dealer_hand;
-
-    GAMESTATE.save(deps.storage, &info.sender, &game)?;
+    let config = CONFIG.load(storage)?;
+    let rules = Rules::of(&config);
+    let mut shoe = SHOE
+        .may_load(storage, (player, token))?
+        .unwrap_or_else(|| Shoe::new(config.decks));
+    // the commitment stands in until the seed is revealed with an action
+    let player_seed = game.player_seed.as_ref().unwrap_or(&game.seed_commitment);
+    let mut rng = random::gen_rng(player_seed, house_seed, pending.made_at);
 
-    Ok(Response::new()
-        .add_attribute(
-            "action",
-            match action {
-                ActionCommand::Stand => "stand",
-                ActionCommand::Hit => "hit",
-                ActionCommand::DoubleDown { amount: _ } => "doubledown",
-            },
-        )
-        .add_attribute("state", "end")
-        .add_attribute("result", result.to_string())
-        .add_attribute("balance_change", balance_change)
-        .add_attribute("judge", judge.to_string()))
-}
+    let mut res = Response::new();
+    let turn = match pending.made_for {
+        Move::Deal => {
+            let turn = play::deal(&mut game, &rules, &mut shoe, &mut rng);
+            res = res
+                .add_attribute("dealer_cards", hand_to_string(&game.dealer_hand))
+                .add_attribute("player_cards", hand_to_string(&game.player_hands[0].cards));
 
-fn exec_bet(
-    storage: &mut dyn Storage,
-    info: &MessageInfo,
-    amount: Uint128,
-) -> Result<Vault, ContractError> {
-    VAULT.update(storage, &info.sender, |vault| match vault {
-        Some(mut v) => {
-            if amount > v.balance {
-                return Err(ContractError::InsufficientBalance { balance: v.balance });
+            let mut side_payout = Uint128::new(0);
+            if !game.perfect_pairs_amount.is_zero() {
+                let pair = game::judge_perfect_pairs(&game.player_hands[0].cards);
+                let table = &config.perfect_pairs;
+                let payout = game.perfect_pairs_amount
+                    * match pair {
+                        PairResult::NoPair => Decimal::zero(),
+                        PairResult::Mixed => table.mixed,
+                        PairResult::Colored => table.colored,
+                        PairResult::Perfect => table.perfect,
+                    };
+                side_payout += payout;
+                res = res
+                    .add_attribute("perfect_pairs", pair.to_string())
+                    .add_attribute("perfect_pairs_payout", payout);
             }
 
-            v.balance -= amount;
-            Ok(v)
+            if !game.twenty_one_plus_three_amount.is_zero() {
+                let poker = game::judge_twenty_one_plus_three(
+                    &game.player_hands[0].cards,
+                    &game.dealer_hand,
+                );
+                let table = &config.twenty_one_plus_three;
+                let payout = game.twenty_one_plus_three_amount
+                    * match poker {
+                        PokerJudge::Nothing => Decimal::zero(),
+                        PokerJudge::Flush => table.flush,
+                        PokerJudge::Straight => table.straight,
+                        PokerJudge::ThreeOfAKind => table.three_of_a_kind,
+                        PokerJudge::StraightFlush => table.straight_flush,
+                        PokerJudge::SuitedTrips => table.suited_trips,
+                    };
+                side_payout += payout;
+                res = res
+                    .add_attribute("twenty_one_plus_three", poker.to_string())
+                    .add_attribute("twenty_one_plus_three_payout", payout);
+            }
+            // what the side bets did not win stays reserved with the round
+            release(storage, player, token, side_payout, side_payout)?;
+            game.liability -= side_payout;
+            game.side_payout = side_payout;
+            turn
         }
-        None => Err(ContractError::NoSuchAccountExists {}),
-    })
-}
+        Move::Action(action) => {
+            if play::declined_into_blackjack(&mut game, &rules, &action, &shoe, &mut rng) {
+                // the action is never played, its stake goes back to the vault
+                if let ActionCommand::DoubleDown { amount } | ActionCommand::Split { amount } =
+                    action
+                {
+                    release(storage, player, token, Uint128::new(0), amount)?;
+                }
+                Turn::Over(Closing::Stand)
+            } else {
+                let active = game.active_hand as usize;
+                let turn = play::act(&mut game, &action, &mut shoe, &mut rng);
+                let hand = &game.player_hands[active];
+                res = match (turn, action) {
+                    (Turn::Playing, ActionCommand::Insurance { amount: _ }) => {
+                        res.add_attribute("insurance", GameResult::Loose.to_string())
+                    }
+                    (Turn::Playing, ActionCommand::Split { amount: _ }) => res
+                        .add_attribute("dealer_cards", hand_to_string(&game.dealer_hand))
+                        .add_attribute("player_cards", hand_to_string(&hand.cards))
+                        .add_attribute("hands", game.player_hands.len().to_string()),
+                    (Turn::Playing, _) => res
+                        .add_attribute("dealer_cards", hand_to_string(&game.dealer_hand))
+                        .add_attribute("player_cards", hand_to_string(&hand.cards))
+                        .add_attribute("draw", hand.cards[hand.cards.len() - 1].to_string()),
+                    // play the next hand
+                    (Turn::NextHand, _) => res
+                        .add_attribute("state", "next_hand")
+                        .add_attribute("dealer_cards", hand_to_string(&game.dealer_hand))
+                        .add_attribute(
+                            "player_cards",
+                            hand_to_string(&game.player_hands[active + 1].cards),
+                        ),
+                    (Turn::Over(_), _) => res,
+                };
+                turn
+            }
+        }
+        Move::ForceSettle(timeout_action) => Turn::Over(Closing::from(timeout_action)),
+    };
+
+    if let Turn::Over(closing) = turn {
+        let settlement = settle(&mut game, &config, &mut shoe, &mut rng, closing);
+        let (round_id, transfer) = finish_round(storage, env, player, token, &game, &settlement)?;
+        res = res
+            .add_attribute("state", "end")
+            .add_attributes(settlement.attributes())
+            .add_attribute("round_id", round_id.to_string())
+            .add_messages(transfer);
+    }
+
+    GAMESTATE.save(storage, (player, token), &game)?;
+    SHOE.save(storage, (player, token), &shoe)?;
+
+    Ok(res)
+}
+
+/// The seed revealed so far, or the one sent if it matches the commitment.
+pub(crate) fn reveal_seed(
+    revealed: Option<Binary>,
+    seed: Option<Binary>,
+    commitment: &Binary,
+) -> Result<Binary, ContractError> {
+    match (revealed, seed) {
+        (Some(revealed), None) => Ok(revealed),
+        (Some(revealed), Some(seed)) if revealed == seed => Ok(revealed),
+        (None, Some(seed)) if random::verify_seed(&seed, commitment) => Ok(seed),
+        (None, None) => Err(ContractError::SeedNotRevealed {}),
+        (_, Some(_)) => Err(ContractError::SeedMismatch {}),
+    }
+}
+
+/// Resolve a game left open past the timeout.
+/// A game left by the player closes as configured by `timeout_action`, standing waits on
+/// a house seed like any move. A game left by the house pays the player all it reserved.
+pub fn try_force_settle(
+    deps: DepsMut,
+    env: Env,
+    address: String,
+    token: AssetInfo,
+) -> Result<Response, ContractError> {
+    let player = deps.api.addr_validate(&address)?;
+    let mut game = GAMESTATE
+        .load(deps.storage, (&player, &token))
+        .map_err(|_| ContractError::NoSuchAccountExists {})?;
+
+    if !game.ingame {
+        return Err(ContractError::ActionBeforeBet {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let seconds_left = time_left(&game, &config, &env);
+    if seconds_left > 0 {
+        return Err(ContractError::NotTimedOut { seconds_left });
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "force_settle")
+        .add_attribute("player", player.clone());
+
+    let closing = match game.pending.take() {
+        Some(pending) => {
+            house::cancel_request(deps.storage, &pending);
+            Closing::HouseTimeout
+        }
+        None if config.timeout_action == TimeoutAction::Stand => {
+            let request = SeedRequest::Game {
+                player: player.clone(),
+                token: token.clone(),
+            };
+            game.pending = Some(house::request_seed(
+                deps.storage,
+                &env,
+                Move::ForceSettle(config.timeout_action),
+                &request,
+            )?);
+            game.turn_started = env.block.time;
+            GAMESTATE.save(deps.storage, (&player, &token), &game)?;
+            return Ok(res);
+        }
+        None => Closing::Forfeit,
+    };
+
+    // nothing left to draw, the hole card is never dealt
+    let settlement = judge_hands(&mut game, &config, closing);
+    let (round_id, transfer) =
+        finish_round(deps.storage, &env, &player, &token, &game, &settlement)?;
+    GAMESTATE.save(deps.storage, (&player, &token), &game)?;
+
+    Ok(res
+        .add_attribute("state", "end")
+        .add_attributes(settlement.attributes())
+        .add_attribute("round_id", round_id.to_string())
+        .add_messages(transfer))
+}
+
+/// Keep the house seed a move of the game in progress was drawn from.
+/// Games started before entropy was kept have none to add to.
+fn push_house_seed(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    token: &AssetInfo,
+    house_seed: HouseSeed,
+) -> StdResult<()> {
+    if let Some(mut entropy) = GAME_ENTROPY.may_load(storage, (address, token))? {
+        entropy.house_seeds.push(house_seed);
+        GAME_ENTROPY.save(storage, (address, token), &entropy)?;
+    }
+    Ok(())
+}
+
+/// Seconds until the game can be force settled, zero once it timed out.
+fn time_left(game: &GameState, config: &Config, env: &Env) -> u64 {
+    game.turn_started
+        .plus_seconds(config.timeout)
+        .seconds()
+        .saturating_sub(env.block.time.seconds())
+}
+
+/// Outcome of a finished round.
+pub(crate) struct Settlement {
+    pub(crate) hands: Vec<(GameResult, Judge)>,
+    pub(crate) insurance: Option<GameResult>,
+    /// returned to the player, stakes included
+    pub(crate) payout: Uint128,
+    /// reservation released from the bankroll
+    pub(crate) liability: Uint128,
+}
+
+impl Settlement {
+    pub(crate) fn attributes(&self) -> Vec<(&str, String)> {
+        let results: Vec<String> = self.hands.iter().map(|(r, _)| r.to_string()).collect();
+        let judges: Vec<String> = self.hands.iter().map(|(_, j)| j.to_string()).collect();
+
+        let mut attributes = vec![
+            ("result", results.join(", ")),
+            ("balance_change", self.payout.to_string()),
+            ("judge", judges.join(", ")),
+        ];
+        if let Some(insurance) = &self.insurance {
+            attributes.push(("insurance", insurance.to_string()));
+        }
+        attributes
+    }
+}
+
+/// Let the dealer play and judge each hand.
+pub(crate) fn settle<T: rand::Rng>(
+    game: &mut GameState,
+    config: &Config,
+    shoe: &mut Shoe,
+    rng: &mut T,
+    closing: Closing,
+) -> Settlement {
+    play::dealer_play(game, &Rules::of(config), shoe, rng, closing);
+    judge_hands(game, config, closing)
+}
+
+/// Judge each hand against the dealer's cards as they are.
+/// A house timeout pays all the round reserved.
+pub(crate) fn judge_hands(game: &mut GameState, config: &Config, closing: Closing) -> Settlement {
+    if closing == Closing::HouseTimeout {
+        let hands = game
+            .player_hands
+            .iter()
+            .map(|hand| {
+                let judge = Judge::HouseTimeout(game::calc_score(&hand.cards));
+                (GameResult::from(judge), judge)
+            })
+            .collect();
+        let insurance = Some(GameResult::Win).filter(|_| !game.insurance_amount.is_zero());
+        let liability = std::mem::take(&mut game.liability);
+        game.ingame = false;
+
+        return Settlement {
+            hands,
+            insurance,
+            payout: liability,
+            liability,
+        };
+    }
+
+    let payout_table = &config.payout_table;
+    let mut payout = Uint128::new(0);
+
+    // insurance pays when the hole card makes blackjack
+    let mut insurance = None;
+    if !game.insurance_amount.is_zero() {
+        insurance = Some(if game::dealer_blackjack(&game.dealer_hand) {
+            payout += game.insurance_amount * payout_table.insurance;
+            GameResult::Win
+        } else {
+            GameResult::Loose
+        });
+    }
+
+    // each hand settles its own bet
+    let split = game.player_hands.len() > 1;
+    let mut hands = vec![];
+    for hand in game.player_hands.iter() {
+        let judge = if closing == Closing::Surrender {
+            Judge::Surrender(game::calc_score(&hand.cards))
+        } else if closing == Closing::Forfeit {
+            Judge::Forfeit(game::calc_score(&hand.cards))
+        } else if split {
+            game::judge_split(&game.dealer_hand, &hand.cards)
+        } else {
+            game::judge(&game.dealer_hand, &hand.cards)
+        };
+
+        let result = GameResult::from(judge);
+
+        // change balance
+        payout += payout_table.payout(hand.bet_amount, result);
+
+        hands.push((result, judge));
+    }
+
+    game.ingame = false;
+
+    Settlement {
+        hands,
+        insurance,
+        payout,
+        liability: std::mem::take(&mut game.liability),
+    }
+}
+
+/// Move stakes into the bankroll and reserve the worst-case payout of a bet.
+pub(crate) fn reserve(
+    storage: &mut dyn Storage,
+    token: &AssetInfo,
+    stake: Uint128,
+    liability: Uint128,
+) -> Result<Bankroll, ContractError> {
+    BANKROLL.update(storage, token, |bankroll| {
+        let mut bankroll = bankroll.ok_or(ContractError::InvalidState {})?;
+        bankroll.balance += stake;
+        if liability > bankroll.available() {
+            return Err(ContractError::InsufficientBankroll {});
+        }
+        bankroll.liabilities += liability;
+        Ok(bankroll)
+    })
+}
+
+/// Release a reservation and pay the player out of the bankroll.
+pub(crate) fn release(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    token: &AssetInfo,
+    liability: Uint128,
+    payout: Uint128,
+) -> StdResult<Vault> {
+    BANKROLL.update(storage, token, |bankroll| -> StdResult<_> {
+        let mut bankroll = bankroll.unwrap_or_default();
+        bankroll.liabilities = bankroll.liabilities.checked_sub(liability)?;
+        bankroll.balance = bankroll.balance.checked_sub(payout)?;
+        Ok(bankroll)
+    })?;
+    credit(storage, address, token, payout)
+}
+
+/// Settle the round with the bankroll and keep it in the player's history.
+/// Returns the round id and the payout transfer when the player auto-withdraws.
+fn finish_round(
+    storage: &mut dyn Storage,
+    env: &Env,
+    address: &Addr,
+    token: &AssetInfo,
+    game: &GameState,
+    settlement: &Settlement,
+) -> Result<(u64, Option<CosmosMsg>), ContractError> {
+    release(
+        storage,
+        address,
+        token,
+        settlement.liability,
+        settlement.payout,
+    )?;
+    let transfer = if game.auto_withdraw && !settlement.payout.is_zero() {
+        Some(withdraw(storage, address, token, settlement.payout)?)
+    } else {
+        None
+    };
+
+    let round = Round {
+        round_id: next_round_id(storage)?,
+        token: token.clone(),
+        dealer_hand: game.dealer_hand.clone(),
+        player_hands: game.player_hands.clone(),
+        results: settlement
+            .hands
+            .iter()
+            .map(|(result, judge)| HandResult {
+                result: *result,
+                judge: *judge,
+            })
+            .collect(),
+        insurance: settlement.insurance,
+        bet: game.total_bet_amount,
+        side_bets: game.insurance_amount
+            + game.perfect_pairs_amount
+            + game.twenty_one_plus_three_amount,
+        payout: settlement.payout + game.side_payout,
+        height: env.block.height,
+    };
+    record_round(storage, address, &round)?;
+
+    if let Some(mut entropy) = GAME_ENTROPY.may_load(storage, (address, token))? {
+        entropy.players[0].seed = game.player_seed.clone();
+        GAME_ENTROPY.remove(storage, (address, token));
+        ROUND_ENTROPY.save(storage, (address, U64Key::new(round.round_id)), &entropy)?;
+    }
+
+    Ok((round.round_id, transfer))
+}
+
+/// Id of the round being finished, shared by all players.
+pub(crate) fn next_round_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let round_id = ROUND_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    ROUND_COUNT.save(storage, &round_id)?;
+    Ok(round_id)
+}
+
+/// Keep a finished round in the player's history and count it in the stats.
+pub(crate) fn record_round(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    round: &Round,
+) -> StdResult<()> {
+    let token = &round.token;
+    HISTORY.save(storage, (address, U64Key::new(round.round_id)), round)?;
+
+    let wagered = round.bet + round.side_bets;
+    let mut stats = STATS.may_load(storage, token)?.unwrap_or_default();
+    stats.rounds += 1;
+    stats.wagered += wagered;
+    stats.paid_out += round.payout;
+    for hand in round.results.iter() {
+        stats.results.add(hand.result);
+        stats.judges.add(hand.judge);
+    }
+    STATS.save(storage, token, &stats)?;
+
+    let players = player_stats();
+    let mut player = players
+        .may_load(storage, (token, address))?
+        .unwrap_or_default();
+    player.rounds += 1;
+    player.wagered += wagered;
+    player.paid_out += round.payout;
+    players.save(storage, (token, address), &player)?;
+
+    Ok(())
+}
+
+pub(crate) fn credit(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    asset: &AssetInfo,
+    amount: Uint128,
+) -> StdResult<Vault> {
+    VAULT.update(
+        storage,
+        (address, asset),
+        |vault: Option<Vault>| -> StdResult<Vault> {
+            let mut vault = vault.unwrap();
+            vault.balance = vault.balance.saturating_add(amount);
+            Ok(vault)
+        },
+    )
+}
+
+pub(crate) fn exec_bet(
+    storage: &mut dyn Storage,
+    info: &MessageInfo,
+    asset: &AssetInfo,
+    amount: Uint128,
+) -> Result<Vault, ContractError> {
+    VAULT.update(storage, (&info.sender, asset), |vault| match vault {
+        Some(mut v) => {
+            if amount > v.balance {
+                return Err(ContractError::InsufficientBalance { balance: v.balance });
+            }
+
+            v.balance -= amount;
+            Ok(v)
+        }
+        None => Err(ContractError::NoSuchAccountExists {}),
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME && stored.contract != migration::v0_1::CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            contract: stored.contract,
+        });
+    }
+    let version = parse_version(&stored.version).ok_or_else(|| ContractError::InvalidVersion {
+        version: stored.version.clone(),
+    })?;
+    if Some(version) > parse_version(CONTRACT_VERSION) {
+        return Err(ContractError::CannotDowngrade {
+            version: stored.version,
+        });
+    }
+
+    let mut res = Response::new().add_attribute("action", "migrate");
+    if version < (0, 2, 0) {
+        let refunded = migration::migrate_from_v0_1(deps.storage)?;
+        res = res.add_attribute("refunded_games", refunded.to_string());
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(res
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::GetDeposit { address } => to_binary(&query_deposit(deps, address)?),
-        QueryMsg::GetGameState { address } => to_binary(&query_gamestate(deps, address)?),
+        QueryMsg::GetDeposit { address, asset } => to_binary(&query_deposit(deps, address, asset)?),
+        QueryMsg::GetGameState { address, token } => {
+            to_binary(&query_gamestate(deps, _env, address, token)?)
+        }
+        QueryMsg::GetConfig {} => to_binary(&query_config(deps)?),
+        QueryMsg::GetTokens {} => to_binary(&query_tokens(deps)?),
+        QueryMsg::GetState {} => to_binary(&query_state(deps)?),
+        QueryMsg::GetShoe { address, token } => to_binary(&query_shoe(deps, address, token)?),
+        QueryMsg::GetBankroll { token } => to_binary(&query_bankroll(deps, token)?),
+        QueryMsg::GetLiquidity { address, token } => {
+            to_binary(&query_liquidity(deps, address, token)?)
+        }
+        QueryMsg::GetHistory {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_history(deps, address, start_after, limit)?),
+        QueryMsg::GetStats { token } => to_binary(&query_stats(deps, token)?),
+        QueryMsg::GetLeaderboard {
+            token,
+            rank_by,
+            start_after,
+            limit,
+        } => to_binary(&query_leaderboard(
+            deps,
+            token,
+            rank_by,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::GetTable { table_id } => to_binary(&table::query_table(deps, _env, table_id)?),
+        QueryMsg::GetTables { start_after, limit } => {
+            to_binary(&table::query_tables(deps, _env, start_after, limit)?)
+        }
+        QueryMsg::GetTournament { tournament_id } => {
+            to_binary(&tournament::query_tournament(deps, tournament_id)?)
+        }
+        QueryMsg::GetTournamentEntry {
+            tournament_id,
+            address,
+        } => to_binary(&tournament::query_entry(deps, tournament_id, address)?),
+        QueryMsg::GetStandings { tournament_id } => {
+            to_binary(&tournament::query_standings(deps, tournament_id)?)
+        }
+        QueryMsg::VerifyRound { address, round_id } => {
+            to_binary(&verify::query_verify_round(deps, address, round_id)?)
+        }
+        QueryMsg::GetHouseChain {} => to_binary(&house::query_house_chain(deps)?),
+        QueryMsg::GetHouseSeed { index } => to_binary(&house::query_house_seed(deps, index)?),
     }
 }
 
-fn query_deposit(deps: Deps, address: String) -> StdResult<DepositResponse> {
+fn query_deposit(deps: Deps, address: String, asset: AssetInfo) -> StdResult<DepositResponse> {
     let address = deps.api.addr_validate(&address)?;
-    let vault = VAULT.may_load(deps.storage, &address)?;
+    let vault = VAULT.may_load(deps.storage, (&address, &asset))?;
     let deposit = if let Some(k) = vault {
         k.balance
     } else {
@@ -331,86 +1539,240 @@ fn query_deposit(deps: Deps, address: String) -> StdResult<DepositResponse> {
     };
     Ok(DepositResponse {
         address: address.to_string(),
+        asset,
         deposit,
     })
 }
 
-fn query_gamestate(deps: Deps, address: String) -> StdResult<GameStateResponce> {
+fn query_gamestate(
+    deps: Deps,
+    env: Env,
+    address: String,
+    token: AssetInfo,
+) -> StdResult<GameStateResponce> {
     let address = deps.api.addr_validate(&address)?;
-    let state = GAMESTATE.load(deps.storage, &address)?;
+    let state = GAMESTATE.load(deps.storage, (&address, &token))?;
+    let mut time_left = None;
+    if state.ingame {
+        time_left = Some(self::time_left(&state, &CONFIG.load(deps.storage)?, &env));
+    }
+    let dealer_score = game::calc_soft_score(&state.dealer_hand);
+    let player_scores = state
+        .player_hands
+        .iter()
+        .map(|h| game::calc_soft_score(&h.cards))
+        .collect();
 
-    Ok(GameStateResponce { state })
+    Ok(GameStateResponce {
+        state,
+        dealer_score,
+        player_scores,
+        time_left,
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::card::BJCard::*;
-    use crate::card::Hand;
-    use crate::msg::GameStateResponce;
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
 
-    use super::*;
-    use cosmwasm_std::testing::{
-        mock_dependencies, mock_dependencies_with_balances, mock_env, mock_info, MockApi,
-        MockQuerier, MockStorage,
+    Ok(ConfigResponse { config })
+}
+
+fn query_tokens(deps: Deps) -> StdResult<TokensResponse> {
+    let tokens = TOKENS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (token, config) = item?;
+            Ok(TokenResponse {
+                token: AssetInfo::from_key(&token)?,
+                bet_limits: config.bet_limits,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TokensResponse { tokens })
+}
+
+fn query_state(deps: Deps) -> StdResult<StateResponse> {
+    let state = STATE.load(deps.storage)?;
+
+    Ok(StateResponse {
+        owner: state.owner.to_string(),
+        pending_owner: state.pending_owner.map(|a| a.to_string()),
+        paused: state.paused,
+    })
+}
+
+fn query_shoe(deps: Deps, address: String, token: AssetInfo) -> StdResult<ShoeResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let shoe = SHOE.load(deps.storage, (&address, &token))?;
+
+    Ok(ShoeResponse {
+        remaining: shoe.len(),
+        dealt: shoe.dealt(),
+        shoe,
+    })
+}
+
+fn query_bankroll(deps: Deps, token: AssetInfo) -> StdResult<BankrollResponse> {
+    let bankroll = BANKROLL.load(deps.storage, &token)?;
+    Ok(BankrollResponse {
+        token,
+        balance: bankroll.balance,
+        liabilities: bankroll.liabilities,
+        available: bankroll.available(),
+        total_shares: bankroll.total_shares,
+    })
+}
+
+fn query_liquidity(deps: Deps, address: String, token: AssetInfo) -> StdResult<LiquidityResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let bankroll = BANKROLL.load(deps.storage, &token)?;
+    let shares = shares_of(deps.storage, &address, &token, &bankroll)?;
+    let value = if bankroll.total_shares.is_zero() {
+        Uint128::new(0)
+    } else {
+        bankroll
+            .balance
+            .multiply_ratio(shares, bankroll.total_shares)
     };
-    use cosmwasm_std::{coins, from_binary, OwnedDeps};
+    Ok(LiquidityResponse {
+        address: address.to_string(),
+        shares,
+        value,
+    })
+}
 
-    fn init_with_balance() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
-        let mut deps = mock_dependencies_with_balances(&[("creator", &coins(2, "token"))]);
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
 
-        let msg = InstantiateMsg {
-            cw20_address: "token0000".to_string(),
-        };
-        let info = mock_info("creator", &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+fn query_history(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<HistoryResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let end = start_after.map(Bound::exclusive_int);
 
-        // deposit
-        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: "user0000".to_string(),
-            amount: Uint128::new(1000),
-            msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
-        });
-        let _res = execute(deps.as_mut(), mock_env(), mock_info("token0000", &[]), msg).unwrap();
+    let rounds = HISTORY
+        .prefix(&address)
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|item| item.map(|(_, round)| round))
+        .collect::<StdResult<Vec<_>>>()?;
 
-        deps
-    }
+    Ok(HistoryResponse {
+        address: address.to_string(),
+        rounds,
+    })
+}
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies(&[]);
+fn query_stats(deps: Deps, token: AssetInfo) -> StdResult<StatsResponse> {
+    let stats = STATS.may_load(deps.storage, &token)?.unwrap_or_default();
+    let house_edge = if stats.wagered.is_zero() {
+        Decimal::zero().to_string()
+    } else if stats.wagered >= stats.paid_out {
+        Decimal::from_ratio(stats.wagered - stats.paid_out, stats.wagered).to_string()
+    } else {
+        format!(
+            "-{}",
+            Decimal::from_ratio(stats.paid_out - stats.wagered, stats.wagered)
+        )
+    };
 
-        let msg = InstantiateMsg {
-            cw20_address: "token0000".to_string(),
-        };
-        let info = mock_info("creator", &[]);
+    Ok(StatsResponse { stats, house_edge })
+}
+
+fn query_leaderboard(
+    deps: Deps,
+    token: AssetInfo,
+    rank_by: RankBy,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<LeaderboardResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let players = player_stats();
+    let index = match rank_by {
+        RankBy::Profit => &players.idx.profit,
+        RankBy::Volume => &players.idx.volume,
+    };
+
+    let end = match start_after {
+        Some(address) => {
+            let address = deps.api.addr_validate(&address)?;
+            let player = players.load(deps.storage, (&token, &address))?;
+            let score = match rank_by {
+                RankBy::Profit => player.profit_key(),
+                RankBy::Volume => player.wagered.u128(),
+            };
+            // relative to the token prefix
+            let pk = (&token, &address).joined_key();
+            Some(Bound::exclusive((U128Key::new(score), pk).joined_key()))
+        }
+        None => None,
+    };
+
+    let entries = index
+        .sub_prefix(stats_prefix(&token))
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|item| {
+            let (pk, player) = item?;
+            let profit = if player.paid_out >= player.wagered {
+                (player.paid_out - player.wagered).to_string()
+            } else {
+                format!("-{}", player.wagered - player.paid_out)
+            };
+            let (_, address) = split_stats_key(&pk);
+            Ok(LeaderboardEntry {
+                address: String::from_utf8(address.to_vec())
+                    .map_err(|_| StdError::invalid_utf8("player_stats key"))?,
+                rounds: player.rounds,
+                wagered: player.wagered,
+                paid_out: player.paid_out,
+                profit,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(LeaderboardResponse { entries })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Rank::*;
+    use crate::card::{hand, ranks, BJCard, Rank, Suit, CARDLIST};
+    use crate::msg::GameStateResponce;
+    use crate::state::{PayoutTable, PerfectPairsTable, TimeoutAction, TwentyOnePlusThreeTable};
+
+    use crate::testing::{
+        self, execute, hook, init, init_with_balance, instantiate_msg, provide_liquidity, seed,
+        seed_commitment, TestDeps,
+    };
+
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockStorage};
+    use cosmwasm_std::{coins, from_binary, SubMsg};
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+    #[test]
+    fn proper_initialization() {
+        let deps = init(instantiate_msg());
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(DEFAULT_DECKS, config.decks);
     }
 
     #[test]
     fn deposit() {
-        let mut deps = mock_dependencies(&[]);
-
-        let msg = InstantiateMsg {
-            cw20_address: "token0000".to_string(),
-        };
-        let info = mock_info("creator", &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        let mut deps = init(instantiate_msg());
 
         // deposit
-        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: "user0000".to_string(),
-            amount: Uint128::new(1000),
-            msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
-        });
-        let _res = execute(deps.as_mut(), mock_env(), mock_info("token0000", &[]), msg).unwrap();
+        testing::deposit(&mut deps, "user0000", 1000);
 
         let msg = QueryMsg::GetDeposit {
             address: "user0000".to_string(),
+            asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
         };
         let query_deposit: DepositResponse =
             from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
@@ -418,20 +1780,19 @@ mod tests {
         assert_eq!(
             DepositResponse {
                 address: "user0000".to_string(),
+                asset: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("token0000"),
+                },
                 deposit: Uint128::new(1000)
             },
             query_deposit
         );
 
         // deposit again
-        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: "user0000".to_string(),
-            amount: Uint128::new(1000),
-            msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
-        });
-        let _res = execute(deps.as_mut(), mock_env(), mock_info("token0000", &[]), msg).unwrap();
+        testing::deposit(&mut deps, "user0000", 1000);
         let msg = QueryMsg::GetDeposit {
             address: "user0000".to_string(),
+            asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
         };
         let query_deposit: DepositResponse =
             from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
@@ -439,6 +1800,9 @@ mod tests {
         assert_eq!(
             DepositResponse {
                 address: "user0000".to_string(),
+                asset: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("token0000"),
+                },
                 deposit: Uint128::new(2000)
             },
             query_deposit
@@ -446,6 +1810,7 @@ mod tests {
 
         let msg = QueryMsg::GetDeposit {
             address: "other0000".to_string(),
+            asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
         };
         let query_deposit: DepositResponse =
             from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
@@ -453,6 +1818,9 @@ mod tests {
         assert_eq!(
             DepositResponse {
                 address: "other0000".to_string(),
+                asset: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("token0000"),
+                },
                 deposit: Uint128::new(0)
             },
             query_deposit
@@ -461,30 +1829,36 @@ mod tests {
 
     #[test]
     fn bet() {
-        let mut deps = mock_dependencies(&[]);
-
-        let msg = InstantiateMsg {
-            cw20_address: "token0000".to_string(),
-        };
-        let info = mock_info("creator", &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        let mut deps = init(instantiate_msg());
 
         // deposit
-        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: "user0000".to_string(),
-            amount: Uint128::new(1000),
-            msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
-        });
-        let _res = execute(deps.as_mut(), mock_env(), mock_info("token0000", &[]), msg).unwrap();
+        testing::deposit(&mut deps, "user0000", 1000);
+
+        // the bankroll must cover a doubled down win of 400
+        provide_liquidity(&mut deps, 299);
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::InsufficientBankroll {}, err);
+        provide_liquidity(&mut deps, 1);
 
         // bet
         let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
             amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
         };
         let _res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
         let msg = QueryMsg::GetDeposit {
             address: "user0000".to_string(),
+            asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
         };
         let query_deposit: DepositResponse =
             from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
@@ -492,7 +1866,11 @@ mod tests {
 
         // bet is not allowed while in game
         let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
             amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
         };
         let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
         assert_eq!(ContractError::BetAfterStart {}, res);
@@ -501,7 +1879,11 @@ mod tests {
         let mut deps = init_with_balance();
 
         let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
             amount: Uint128::new(1001),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
         };
         let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
         assert_eq!(
@@ -513,7 +1895,11 @@ mod tests {
 
         // other0000 fail to bet
         let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
             amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
         };
         let res = execute(deps.as_mut(), mock_env(), mock_info("other0000", &[]), msg).unwrap_err();
         assert_eq!(ContractError::NoSuchAccountExists {}, res);
@@ -525,58 +1911,182 @@ mod tests {
 
         // action before bet is not allowed
         let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
             action: ActionCommand::Stand,
+            seed: Some(seed("user0000")),
         };
         let ret = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
         assert_eq!(ContractError::NoSuchAccountExists {}, ret);
 
         let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
             amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
         };
         let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
 
         // other0000 fail to action
         let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
             action: ActionCommand::Stand,
+            seed: Some(seed("user0000")),
         };
         let res = execute(deps.as_mut(), mock_env(), mock_info("other0000", &[]), msg).unwrap_err();
         assert_eq!(ContractError::NoSuchAccountExists {}, res);
 
         let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
             action: ActionCommand::Stand,
+            seed: Some(seed("user0000")),
         };
         let _ret = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
     }
 
-    struct CreateOption {
-        d: Hand,
-        p: Hand,
-        amount: u128,
-        ingame: bool,
-    }
-
-    impl Default for CreateOption {
-        fn default() -> Self {
-            Self {
-                d: vec![],
-                p: vec![],
-                amount: 100,
-                ingame: true,
-            }
-        }
-    }
+    #[test]
+    fn reveal_seed() {
+        let mut deps = init_with_balance();
 
-    fn create_game_storage(mut s: MockStorage, option: CreateOption) -> MockStorage {
-        let state = GameState {
-            ingame: option.ingame,
-            total_bet_amount: Uint128::new(option.amount),
-            dealer_hand: option.d,
-            player_hand: option.p,
+        // commitment must be a sha256 hash
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::InvalidCommitment {}, err);
+
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Seven],
+                p: vec![Two, Three],
+                ..Default::default()
+            },
+        );
+
+        // first action without seed
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Hit,
+            seed: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::SeedNotRevealed {}, err);
+
+        // seed which does not match the commitment
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Hit,
+            seed: Some(Binary::from(b"other seed")),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::SeedMismatch {}, err);
+
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Hit,
+            seed: Some(seed("user0000")),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+
+        // once revealed, the seed can be omitted but not changed
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Hit,
+            seed: Some(Binary::from(b"other seed")),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::SeedMismatch {}, err);
+
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Stand,
+            seed: None,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+    }
+
+    struct CreateOption {
+        d: Vec<Rank>,
+        /// the only rank left in the shoe, so the peek and the hole card are known
+        shoe: Option<Rank>,
+        p: Vec<Rank>,
+        amount: u128,
+        insurance: u128,
+        ingame: bool,
+    }
+
+    impl Default for CreateOption {
+        fn default() -> Self {
+            Self {
+                d: vec![],
+                shoe: None,
+                p: vec![],
+                amount: 100,
+                insurance: 0,
+                ingame: true,
+            }
+        }
+    }
+
+    fn create_game_storage(mut s: MockStorage, option: CreateOption) -> MockStorage {
+        let state = GameState {
+            ingame: option.ingame,
+            total_bet_amount: Uint128::new(option.amount),
+            dealer_hand: hand(&option.d),
+            dealer_blackjack: None,
+            player_hands: vec![PlayerHand {
+                cards: hand(&option.p),
+                bet_amount: Uint128::new(option.amount),
+            }],
+            active_hand: 0,
+            insurance_amount: Uint128::new(option.insurance),
+            perfect_pairs_amount: Uint128::new(0),
+            twenty_one_plus_three_amount: Uint128::new(0),
+            side_payout: Uint128::new(0),
+            liability: Uint128::new(0),
+            seed_commitment: seed_commitment("user0000"),
+            player_seed: None,
+            pending: None,
+            started_height: 12_345,
+            started_at: mock_env().block.time,
+            turn_started: mock_env().block.time,
+            auto_withdraw: false,
         };
 
         GAMESTATE
-            .save(&mut s, &Addr::unchecked("user0000"), &state)
+            .save(
+                &mut s,
+                (&Addr::unchecked("user0000"), &testing::token("token0000")),
+                &state,
+            )
+            .unwrap();
+        if let Some(rank) = option.shoe {
+            let shoe = Shoe {
+                decks: DEFAULT_DECKS,
+                remaining: CARDLIST
+                    .iter()
+                    .map(|c| {
+                        if c.rank == rank {
+                            DEFAULT_DECKS as u32
+                        } else {
+                            0
+                        }
+                    })
+                    .collect(),
+                in_play: vec![],
+            };
+            SHOE.save(
+                &mut s,
+                (&Addr::unchecked("user0000"), &testing::token("token0000")),
+                &shoe,
+            )
             .unwrap();
+        }
         s
     }
 
@@ -586,7 +2096,7 @@ mod tests {
         deps.storage = create_game_storage(
             deps.storage,
             CreateOption {
-                d: vec![Five],
+                d: vec![Ten, Seven],
                 p: vec![Two, Three],
                 ..Default::default()
             },
@@ -594,7 +2104,9 @@ mod tests {
 
         // loose
         let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
             action: ActionCommand::Stand,
+            seed: Some(seed("user0000")),
         };
         let _ret = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
 
@@ -604,6 +2116,7 @@ mod tests {
                 mock_env(),
                 QueryMsg::GetDeposit {
                     address: "user0000".to_string(),
+                    asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
                 },
             )
             .unwrap(),
@@ -613,7 +2126,9 @@ mod tests {
 
         // stand after stand should be failed
         let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
             action: ActionCommand::Stand,
+            seed: Some(seed("user0000")),
         };
         let ret = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
         assert_eq!(ContractError::ActionBeforeBet {}, ret);
@@ -627,18 +2142,22 @@ mod tests {
             CreateOption {
                 d: vec![Seven],
                 p: vec![Ten, Three],
+                shoe: Some(Eight),
                 ..Default::default()
             },
         );
 
         let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
             action: ActionCommand::Hit,
+            seed: Some(seed("user0000")),
         };
         let hit = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
         let hit_arr_draw = &hit.attributes.get(3).expect("no attribute").value;
-        assert_eq!("6", hit_arr_draw);
+        assert_eq!("8♦", hit_arr_draw);
 
         let msg = QueryMsg::GetGameState {
+            token: testing::token("token0000"),
             address: "user0000".to_string(),
         };
         let ret = query(deps.as_ref(), mock_env(), msg).unwrap();
@@ -648,11 +2167,34 @@ mod tests {
             GameState {
                 ingame: true,
                 total_bet_amount: Uint128::new(100),
-                dealer_hand: vec![Seven],
-                player_hand: vec![Ten, Three, Six],
+                dealer_hand: hand(&[Seven]),
+                dealer_blackjack: None,
+                player_hands: vec![PlayerHand {
+                    cards: [
+                        hand(&[Ten, Three]),
+                        vec![BJCard::new(Eight, Suit::Diamonds)]
+                    ]
+                    .concat(),
+                    bet_amount: Uint128::new(100),
+                }],
+                active_hand: 0,
+                insurance_amount: Uint128::new(0),
+                perfect_pairs_amount: Uint128::new(0),
+                twenty_one_plus_three_amount: Uint128::new(0),
+                side_payout: Uint128::new(0),
+                liability: Uint128::new(0),
+                seed_commitment: seed_commitment("user0000"),
+                player_seed: Some(seed("user0000")),
+                pending: None,
+                started_height: 12_345,
+                started_at: mock_env().block.time,
+                turn_started: mock_env().block.time,
+                auto_withdraw: false,
             },
             res.state
         );
+        assert_eq!("hard 7", res.dealer_score.to_string());
+        assert_eq!("hard 21", res.player_scores[0].to_string());
 
         // hit after stand/doubledown is not allowed
         let mut deps = init_with_balance();
@@ -670,7 +2212,9 @@ mod tests {
             mock_env(),
             mock_info("user0000", &[]),
             ExecuteMsg::Action {
+                token: testing::token("token0000"),
                 action: ActionCommand::Stand,
+                seed: Some(seed("user0000")),
             },
         )
         .unwrap();
@@ -680,7 +2224,9 @@ mod tests {
             mock_env(),
             mock_info("user0000", &[]),
             ExecuteMsg::Action {
+                token: testing::token("token0000"),
                 action: ActionCommand::Hit,
+                seed: Some(seed("user0000")),
             },
         )
         .unwrap_err();
@@ -702,9 +2248,11 @@ mod tests {
             mock_env(),
             mock_info("user0000", &[]),
             ExecuteMsg::Action {
+                token: testing::token("token0000"),
                 action: ActionCommand::DoubleDown {
                     amount: Uint128::new(100),
                 },
+                seed: Some(seed("user0000")),
             },
         )
         .unwrap();
@@ -714,7 +2262,9 @@ mod tests {
             mock_env(),
             mock_info("user0000", &[]),
             ExecuteMsg::Action {
+                token: testing::token("token0000"),
                 action: ActionCommand::Hit {},
+                seed: Some(seed("user0000")),
             },
         )
         .unwrap_err();
@@ -733,9 +2283,11 @@ mod tests {
             },
         );
         let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
             action: ActionCommand::DoubleDown {
                 amount: Uint128::new(100),
             },
+            seed: Some(seed("user0000")),
         };
         let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
 
@@ -749,9 +2301,11 @@ mod tests {
             },
         );
         let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
             action: ActionCommand::DoubleDown {
                 amount: Uint128::new(100),
             },
+            seed: Some(seed("user0000")),
         };
         // doubledown after hit is not allowd
         let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
@@ -768,9 +2322,11 @@ mod tests {
             },
         );
         let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
             action: ActionCommand::DoubleDown {
                 amount: Uint128::new(1200),
             },
+            seed: Some(seed("user0000")),
         };
         let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
         assert_eq!(
@@ -790,9 +2346,11 @@ mod tests {
             },
         );
         let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
             action: ActionCommand::DoubleDown {
                 amount: Uint128::new(200),
             },
+            seed: Some(seed("user0000")),
         };
         let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
         assert_eq!(
@@ -804,30 +2362,2098 @@ mod tests {
     }
 
     #[test]
-    fn withdraw() {
+    fn action_split() {
         let mut deps = init_with_balance();
-        let msg = ExecuteMsg::Withdraw {
-            amount: Uint128::new(100),
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Ten, Seven],
+                p: vec![Eight, Eight],
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Split {
+                amount: Uint128::new(100),
+            },
+            seed: Some(seed("user0000")),
         };
         let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
 
-        // other user could not withdraw
-        let msg = ExecuteMsg::Withdraw {
-            amount: Uint128::new(100),
+        let res: GameStateResponce = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetGameState {
+                    token: testing::token("token0000"),
+                    address: "user0000".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(res.state.ingame);
+        assert_eq!(Uint128::new(200), res.state.total_bet_amount);
+        assert_eq!(2, res.state.player_hands.len());
+        for hand in res.state.player_hands.iter() {
+            assert_eq!(Eight, hand.cards[0].rank);
+            assert_eq!(2, hand.cards.len());
+            assert_eq!(Uint128::new(100), hand.bet_amount);
+        }
+
+        let ret: DepositResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetDeposit {
+                    address: "user0000".to_string(),
+                    asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Uint128::new(900), ret.deposit);
+
+        // first hand stands, then the second one
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Stand,
+            seed: None,
         };
-        let err = execute(deps.as_mut(), mock_env(), mock_info("other0000", &[]), msg).unwrap_err();
-        assert_eq!(ContractError::NoSuchAccountExists {}, err);
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("next_hand", res.attributes[1].value);
 
-        // insufficient balance
-        let msg = ExecuteMsg::Withdraw {
-            amount: Uint128::new(10000),
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Stand,
+            seed: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("end", res.attributes[1].value);
+        assert_eq!(2, res.attributes[2].value.split(", ").count());
+
+        // split is only allowed on a pair
+        let mut deps = init_with_balance();
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Seven],
+                p: vec![Ten, King],
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Split {
+                amount: Uint128::new(100),
+            },
+            seed: Some(seed("user0000")),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::SplitNotAllowed {}, err);
+
+        let mut deps = init_with_balance();
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Seven],
+                p: vec![Eight, Eight],
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Split {
+                amount: Uint128::new(200),
+            },
+            seed: Some(seed("user0000")),
         };
         let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
         assert_eq!(
-            ContractError::InsufficientBalance {
-                balance: Uint128::new(900)
+            ContractError::WrongSplitAmount {
+                amount: Uint128::new(100)
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn action_insurance() {
+        let mut deps = init_with_balance();
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Ace],
+                p: vec![Ten, Seven],
+                shoe: Some(Nine),
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Insurance {
+                amount: Uint128::new(50),
+            },
+            seed: Some(seed("user0000")),
+        };
+        // disabled by config
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::InsuranceNotAllowed {}, err);
+
+        CONFIG
+            .update(&mut deps.storage, |mut c| -> StdResult<_> {
+                c.insurance = true;
+                Ok(c)
+            })
+            .unwrap();
+
+        // up to half of the bet
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Insurance {
+                amount: Uint128::new(51),
+            },
+            seed: Some(seed("user0000")),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(
+            ContractError::WrongInsuranceAmount {
+                max: Uint128::new(50)
             },
             err
         );
+
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Insurance {
+                amount: Uint128::new(50),
+            },
+            seed: Some(seed("user0000")),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+
+        let ret: DepositResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetDeposit {
+                    address: "user0000".to_string(),
+                    asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Uint128::new(950), ret.deposit);
+
+        // only once
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Insurance {
+                amount: Uint128::new(50),
+            },
+            seed: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::InsuranceNotAllowed {}, err);
+
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Stand,
+            seed: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "insurance"));
+
+        // dealer not showing an ace
+        let mut deps = init_with_balance();
+        CONFIG
+            .update(&mut deps.storage, |mut c| -> StdResult<_> {
+                c.insurance = true;
+                Ok(c)
+            })
+            .unwrap();
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Ten],
+                p: vec![Ten, Seven],
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Insurance {
+                amount: Uint128::new(50),
+            },
+            seed: Some(seed("user0000")),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::InsuranceNotAllowed {}, err);
+    }
+
+    #[test]
+    fn insurance_payout() {
+        // dealer blackjack: bet is lost, insurance pays 2:1 and the round ends
+        let mut deps = init_with_balance();
+        CONFIG
+            .update(&mut deps.storage, |mut c| -> StdResult<_> {
+                c.insurance = true;
+                Ok(c)
+            })
+            .unwrap();
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Ace],
+                shoe: Some(King),
+                p: vec![Ten, Seven],
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Insurance {
+                amount: Uint128::new(50),
+            },
+            seed: Some(seed("user0000")),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("end", res.attributes[3].value);
+        assert_eq!("Loose", res.attributes[4].value);
+        assert_eq!("150", res.attributes[5].value);
+        assert_eq!("Win", res.attributes[7].value);
+
+        // no blackjack: insurance is lost, the hand is played on
+        let mut deps = init_with_balance();
+        CONFIG
+            .update(&mut deps.storage, |mut c| -> StdResult<_> {
+                c.insurance = true;
+                Ok(c)
+            })
+            .unwrap();
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Ace],
+                shoe: Some(Seven),
+                p: vec![Ten, Nine],
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Insurance {
+                amount: Uint128::new(50),
+            },
+            seed: Some(seed("user0000")),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("Loose", res.attributes[3].value);
+
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Stand,
+            seed: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("200", res.attributes[3].value);
+        assert_eq!("Loose", res.attributes[5].value);
+    }
+
+    #[test]
+    fn perfect_pairs_bet() {
+        // find a seed that deals the player a pair
+        let env = mock_env();
+        let house_seed = testing::house_seed(1);
+        let (commitment, pair) = (0u32..)
+            .map(|i| Binary(random::sha256(&i.to_be_bytes())))
+            .find_map(|c| {
+                let mut rng = random::gen_rng(&c, &house_seed, env.block.time);
+                let mut shoe = Shoe::new(DEFAULT_DECKS);
+                let (d, p) = game::first_deal(&mut shoe, &mut rng);
+                let pair = game::judge_perfect_pairs(&p);
+                let peek = game::peek_blackjack(&d, &shoe, &mut rng);
+                if pair != PairResult::NoPair && peek != Some(true) {
+                    Some((c, pair))
+                } else {
+                    None
+                }
+            })
+            .unwrap();
+        let table = PerfectPairsTable::default();
+        let multiplier = match pair {
+            PairResult::Mixed => table.mixed,
+            PairResult::Colored => table.colored,
+            _ => table.perfect,
+        };
+
+        let mut deps = init_with_balance();
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: commitment,
+            perfect_pairs: Some(Uint128::new(10)),
+            twenty_one_plus_three: None,
+        };
+        let res = execute(deps.as_mut(), env, mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("890", res.attributes[2].value);
+        assert_eq!(pair.to_string(), res.attributes[5].value);
+        assert_eq!(
+            (Uint128::new(10) * multiplier).to_string(),
+            res.attributes[6].value
+        );
+
+        let ret: DepositResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetDeposit {
+                    address: "user0000".to_string(),
+                    asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            Uint128::new(890) + Uint128::new(10) * multiplier,
+            ret.deposit
+        );
+
+        // the side bet is lost without a pair
+        let mut deps = init_with_balance();
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: Some(Uint128::new(10)),
+            twenty_one_plus_three: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("NoPair", res.attributes[5].value);
+        assert_eq!("0", res.attributes[6].value);
+
+        // main and side bet together must be covered by the deposit
+        let mut deps = init_with_balance();
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(1000),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: Some(Uint128::new(1)),
+            twenty_one_plus_three: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(
+            ContractError::InsufficientBalance {
+                balance: Uint128::new(1000)
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn twenty_one_plus_three_bet() {
+        // find a seed that deals a flush or better
+        let env = mock_env();
+        let house_seed = testing::house_seed(1);
+        let (commitment, poker) = (0u32..)
+            .map(|i| Binary(random::sha256(&i.to_be_bytes())))
+            .find_map(|c| {
+                let mut rng = random::gen_rng(&c, &house_seed, env.block.time);
+                let mut shoe = Shoe::new(DEFAULT_DECKS);
+                let (d, p) = game::first_deal(&mut shoe, &mut rng);
+                let poker = game::judge_twenty_one_plus_three(&p, &d);
+                let peek = game::peek_blackjack(&d, &shoe, &mut rng);
+                if poker != PokerJudge::Nothing && peek != Some(true) {
+                    Some((c, poker))
+                } else {
+                    None
+                }
+            })
+            .unwrap();
+        let table = TwentyOnePlusThreeTable::default();
+        let multiplier = match poker {
+            PokerJudge::Flush => table.flush,
+            PokerJudge::Straight => table.straight,
+            PokerJudge::ThreeOfAKind => table.three_of_a_kind,
+            PokerJudge::StraightFlush => table.straight_flush,
+            _ => table.suited_trips,
+        };
+
+        let mut deps = init_with_balance();
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: commitment,
+            perfect_pairs: None,
+            twenty_one_plus_three: Some(Uint128::new(10)),
+        };
+        let res = execute(deps.as_mut(), env, mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("890", res.attributes[2].value);
+        assert_eq!(poker.to_string(), res.attributes[5].value);
+        assert_eq!(
+            (Uint128::new(10) * multiplier).to_string(),
+            res.attributes[6].value
+        );
+
+        let ret: DepositResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetDeposit {
+                    address: "user0000".to_string(),
+                    asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            Uint128::new(890) + Uint128::new(10) * multiplier,
+            ret.deposit
+        );
+
+        // the side bet is lost on nothing
+        let mut deps = init_with_balance();
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: Some(Uint128::new(10)),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("Nothing", res.attributes[5].value);
+        assert_eq!("0", res.attributes[6].value);
+    }
+
+    #[test]
+    fn dealer_peek() {
+        // find a seed that deals the dealer a ten up and finds an ace in the hole
+        let env = mock_env();
+        let house_seed = testing::house_seed(1);
+        let commitment = (0u32..)
+            .map(|i| Binary(random::sha256(&i.to_be_bytes())))
+            .find(|c| {
+                let mut rng = random::gen_rng(c, &house_seed, env.block.time);
+                let mut shoe = Shoe::new(DEFAULT_DECKS);
+                let (d, p) = game::first_deal(&mut shoe, &mut rng);
+                game::calc_score(&d) == 10
+                    && game::peek_blackjack(&d, &shoe, &mut rng) == Some(true)
+                    && !game::is_blackjack(&p)
+            })
+            .unwrap();
+
+        // dealer blackjack ends the round at bet
+        let mut deps = init_with_balance();
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: commitment,
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        let res = execute(deps.as_mut(), env, mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("end", res.attributes[5].value);
+        assert_eq!("Loose", res.attributes[6].value);
+        assert_eq!("0", res.attributes[7].value);
+
+        let res: GameStateResponce = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetGameState {
+                    token: testing::token("token0000"),
+                    address: "user0000".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(!res.state.ingame);
+        assert_eq!(Ace, res.state.dealer_hand[1].rank);
+        assert_eq!(Some(true), res.state.dealer_blackjack);
+
+        // no hole card while in game
+        let mut deps = init_with_balance();
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Seven],
+                shoe: Some(Ten),
+                p: vec![Ten, Six],
+                ..Default::default()
+            },
+        );
+        let res: GameStateResponce = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetGameState {
+                    token: testing::token("token0000"),
+                    address: "user0000".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(vec![Seven], ranks(&res.state.dealer_hand));
+        assert_eq!(None, res.state.dealer_blackjack);
+        assert_eq!("hard 7", res.dealer_score.to_string());
+
+        // drawn when the dealer plays
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Stand,
+            seed: Some(seed("user0000")),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        let res: GameStateResponce = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetGameState {
+                    token: testing::token("token0000"),
+                    address: "user0000".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(vec![Seven, Ten], ranks(&res.state.dealer_hand));
+    }
+
+    #[test]
+    fn action_surrender() {
+        let mut deps = init_with_balance();
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Seven],
+                p: vec![Ten, Six],
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Surrender,
+            seed: Some(seed("user0000")),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("Surrender", res.attributes[2].value);
+        assert_eq!("50", res.attributes[3].value);
+        assert_eq!("Surrender 16", res.attributes[4].value);
+
+        // the dealer turns the hole card but does not draw
+        let res: GameStateResponce = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetGameState {
+                    token: testing::token("token0000"),
+                    address: "user0000".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(!res.state.ingame);
+        assert_eq!(2, res.state.dealer_hand.len());
+        assert_eq!(Seven, res.state.dealer_hand[0].rank);
+
+        // the dealer peeks before a late surrender
+        let mut deps = init_with_balance();
+        CONFIG
+            .update(&mut deps.storage, |mut c| -> StdResult<_> {
+                c.insurance = true;
+                Ok(c)
+            })
+            .unwrap();
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Ace],
+                shoe: Some(King),
+                p: vec![Ten, Six],
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Surrender,
+            seed: Some(seed("user0000")),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("Loose", res.attributes[2].value);
+        assert_eq!("0", res.attributes[3].value);
+
+        // only on the first two cards
+        let mut deps = init_with_balance();
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Seven],
+                p: vec![Two, Three, Four],
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Surrender,
+            seed: Some(seed("user0000")),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::SurrenderNotAllowed {}, err);
+    }
+
+    #[test]
+    fn blackjack_payout() {
+        // 3:2 by default
+        let mut deps = init_with_balance();
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Ten, Seven],
+                p: vec![Ace, King],
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Stand,
+            seed: Some(seed("user0000")),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("Blackjack", res.attributes[2].value);
+        assert_eq!("250", res.attributes[3].value);
+
+        // 6:5 table
+        let mut deps = init_with_balance();
+        let payout_table = PayoutTable {
+            blackjack: Decimal::from_ratio(11u128, 5u128),
+            ..Default::default()
+        };
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            payout_table: Some(payout_table.clone()),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            payout_table: Some(PayoutTable {
+                push: Decimal::from_ratio(2u128, 1u128),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::InvalidPayoutTable {}, err);
+
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            payout_table: Some(payout_table.clone()),
+            ..Default::default()
+        });
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let res: ConfigResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap())
+                .unwrap();
+        assert_eq!(payout_table, res.config.payout_table);
+
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Ten, Seven],
+                p: vec![Ace, King],
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Stand,
+            seed: Some(seed("user0000")),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("220", res.attributes[3].value);
+    }
+
+    #[test]
+    fn bankroll() {
+        let mut deps = init_with_balance();
+        let bankroll = |deps: &TestDeps| -> BankrollResponse {
+            from_binary(
+                &query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::GetBankroll {
+                        token: testing::token("token0000"),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        assert_eq!(Uint128::new(1_000_000), bankroll(&deps).balance);
+
+        // only in the configured token
+        let msg = hook("user0000", 1000, &Cw20HookMsg::ProvideLiquidity {});
+        let err = execute(deps.as_mut(), mock_env(), mock_info("token0001", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+
+        // stakes flow in and the worst case is reserved
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: Some(Uint128::new(10)),
+            twenty_one_plus_three: None,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        let res = bankroll(&deps);
+        assert_eq!(Uint128::new(1_000_110), res.balance);
+        assert_eq!(Uint128::new(660), res.liabilities);
+        assert_eq!(Uint128::new(1_000_110 - 660), res.available);
+
+        // doubling is covered by the reservation
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::DoubleDown {
+                amount: Uint128::new(100),
+            },
+            seed: Some(seed("user0000")),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+
+        // settled: the house holds what the player lost
+        let res = bankroll(&deps);
+        assert_eq!(Uint128::new(0), res.liabilities);
+        let deposit: DepositResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetDeposit {
+                    address: "user0000".to_string(),
+                    asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            Uint128::new(1_001_000),
+            res.balance + deposit.deposit,
+            "no funds created or lost"
+        );
+    }
+
+    #[test]
+    fn liquidity() {
+        let mut deps = init_with_balance();
+        let liquidity = |deps: &TestDeps, address: &str| {
+            let msg = QueryMsg::GetLiquidity {
+                token: testing::token("token0000"),
+                address: address.to_string(),
+            };
+            let res: LiquidityResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res
+        };
+        let bankroll = |deps: &TestDeps| {
+            let res: BankrollResponse = from_binary(
+                &query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::GetBankroll {
+                        token: testing::token("token0000"),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            res
+        };
+
+        // shares at the value of the bankroll
+        let msg = hook("lp0000", 500_000, &Cw20HookMsg::ProvideLiquidity {});
+        let res = execute(deps.as_mut(), mock_env(), mock_info("token0000", &[]), msg).unwrap();
+        assert_eq!("500000", res.attributes[2].value);
+        assert_eq!(Uint128::new(1_500_000), bankroll(&deps).total_shares);
+        assert_eq!(Uint128::new(500_000), liquidity(&deps, "lp0000").value);
+
+        // reserved liquidity stays until the game ends
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        let msg = ExecuteMsg::WithdrawLiquidity {
+            token: testing::token("token0000"),
+            shares: Uint128::new(1_000_000),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!("1000066", res.attributes[2].value);
+        let msg = ExecuteMsg::WithdrawLiquidity {
+            token: testing::token("token0000"),
+            shares: Uint128::new(500_000),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("lp0000", &[]), msg).unwrap_err();
+        assert_eq!(
+            ContractError::LiquidityReserved {
+                available: Uint128::new(500_034 - 400)
+            },
+            err
+        );
+
+        // the house result moves the share value
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Stand,
+            seed: Some(seed("user0000")),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!("Loose", res.attributes[2].value);
+        let balance = bankroll(&deps).balance;
+        assert_eq!(Uint128::new(500_034), balance);
+        let lp = liquidity(&deps, "lp0000");
+        assert_eq!(Uint128::new(500_000), lp.shares);
+        assert_eq!(balance, lp.value);
+
+        // burn for a pro-rata payout
+        let msg = ExecuteMsg::WithdrawLiquidity {
+            token: testing::token("token0000"),
+            shares: Uint128::new(500_001),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("lp0000", &[]), msg).unwrap_err();
+        assert_eq!(
+            ContractError::InsufficientShares {
+                shares: Uint128::new(500_000)
+            },
+            err
+        );
+        let msg = ExecuteMsg::WithdrawLiquidity {
+            token: testing::token("token0000"),
+            shares: Uint128::new(500_000),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("lp0000", &[]), msg).unwrap();
+        assert_eq!(lp.value.to_string(), res.attributes[2].value);
+        assert_eq!(1, res.messages.len());
+        assert_eq!(Uint128::new(0), liquidity(&deps, "lp0000").shares);
+        assert_eq!(Uint128::new(0), bankroll(&deps).total_shares);
+        assert_eq!(Uint128::new(0), bankroll(&deps).balance);
+
+        // a balance left without shares goes to the owner, not the next provider
+        BANKROLL
+            .update(deps.as_mut().storage, &testing::token("token0000"), |b| {
+                let mut bankroll: Bankroll = b.unwrap();
+                bankroll.balance = Uint128::new(1000);
+                StdResult::Ok(bankroll)
+            })
+            .unwrap();
+        let msg = hook("lp0000", 500, &Cw20HookMsg::ProvideLiquidity {});
+        let res = execute(deps.as_mut(), mock_env(), mock_info("token0000", &[]), msg).unwrap();
+        assert_eq!("500", res.attributes[2].value);
+        assert_eq!(Uint128::new(1500), bankroll(&deps).total_shares);
+        assert_eq!(Uint128::new(500), liquidity(&deps, "lp0000").value);
+        assert_eq!(Uint128::new(1000), liquidity(&deps, "creator").value);
+
+        // too little for a share mints none
+        let set_balance = |deps: &mut TestDeps, balance| {
+            BANKROLL
+                .update(deps.as_mut().storage, &testing::token("token0000"), |b| {
+                    let mut bankroll: Bankroll = b.unwrap();
+                    bankroll.balance = Uint128::new(balance);
+                    StdResult::Ok(bankroll)
+                })
+                .unwrap();
+        };
+        set_balance(&mut deps, 3000);
+        let msg = hook("lp0001", 1, &Cw20HookMsg::ProvideLiquidity {});
+        let err = execute(deps.as_mut(), mock_env(), mock_info("token0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::LiquidityTooSmall {}, err);
+
+        // shares left on a bankroll wiped out are worthless, the next provider starts anew
+        set_balance(&mut deps, 0);
+        let msg = hook("lp0001", 700, &Cw20HookMsg::ProvideLiquidity {});
+        let res = execute(deps.as_mut(), mock_env(), mock_info("token0000", &[]), msg).unwrap();
+        assert_eq!("700", res.attributes[2].value);
+        assert_eq!(Uint128::new(700), bankroll(&deps).total_shares);
+        assert_eq!(Uint128::new(700), liquidity(&deps, "lp0001").value);
+        assert_eq!(Uint128::new(0), liquidity(&deps, "lp0000").shares);
+        let msg = ExecuteMsg::WithdrawLiquidity {
+            token: testing::token("token0000"),
+            shares: Uint128::new(500),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("lp0000", &[]), msg).unwrap_err();
+        assert_eq!(
+            ContractError::InsufficientShares {
+                shares: Uint128::new(0)
+            },
+            err
+        );
+
+        // a new position after it counts again
+        let msg = hook("lp0000", 70, &Cw20HookMsg::ProvideLiquidity {});
+        execute(deps.as_mut(), mock_env(), mock_info("token0000", &[]), msg).unwrap();
+        assert_eq!(Uint128::new(70), liquidity(&deps, "lp0000").shares);
+    }
+
+    #[test]
+    fn bet_limits() {
+        let mut deps = init_with_balance();
+        let bet = |deps: &mut TestDeps, amount| {
+            let msg = ExecuteMsg::Bet {
+                token: testing::token("token0000"),
+                amount: Uint128::new(amount),
+                seed_commitment: seed_commitment("user0000"),
+                perfect_pairs: None,
+                twenty_one_plus_three: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg)
+        };
+
+        assert_eq!(
+            ContractError::BetAmountZero {},
+            bet(&mut deps, 0).unwrap_err()
+        );
+
+        // owner only, and the limits must make sense
+        let limits = BetLimits {
+            min_bet: Uint128::new(10),
+            max_bet: Some(Uint128::new(500)),
+            max_bankroll_ratio: None,
+        };
+        let msg = ExecuteMsg::AddToken {
+            token: testing::token("token0000"),
+            bet_limits: Some(limits.clone()),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+        let msg = ExecuteMsg::AddToken {
+            token: testing::token("token0000"),
+            bet_limits: Some(BetLimits {
+                max_bet: Some(Uint128::new(5)),
+                ..limits.clone()
+            }),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::InvalidBetLimits {}, err);
+        let msg = ExecuteMsg::AddToken {
+            token: testing::token("token0000"),
+            bet_limits: Some(limits.clone()),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        assert_eq!(
+            ContractError::BetTooSmall {
+                min_bet: Uint128::new(10)
+            },
+            bet(&mut deps, 9).unwrap_err()
+        );
+        assert_eq!(
+            ContractError::BetTooLarge {
+                max_bet: Uint128::new(500)
+            },
+            bet(&mut deps, 501).unwrap_err()
+        );
+
+        // a bet may reach 0.005% of the available bankroll of 1,000,000
+        let msg = ExecuteMsg::AddToken {
+            token: testing::token("token0000"),
+            bet_limits: Some(BetLimits {
+                max_bankroll_ratio: Some(Decimal::from_ratio(5u128, 100_000u128)),
+                ..limits
+            }),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(
+            ContractError::BetExceedsBankrollLimit {
+                max_bet: Uint128::new(50)
+            },
+            bet(&mut deps, 51).unwrap_err()
+        );
+        bet(&mut deps, 50).unwrap();
+
+        // top-ups as well
+        let mut deps = init_with_balance();
+        deps.storage = create_game_storage(
+            deps.storage,
+            CreateOption {
+                d: vec![Seven],
+                p: vec![Five, Six],
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::AddToken {
+            token: testing::token("token0000"),
+            bet_limits: Some(BetLimits {
+                min_bet: Uint128::new(1),
+                max_bet: Some(Uint128::new(50)),
+                max_bankroll_ratio: None,
+            }),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::DoubleDown {
+                amount: Uint128::new(100),
+            },
+            seed: Some(seed("user0000")),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(
+            ContractError::BetTooLarge {
+                max_bet: Uint128::new(50)
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn shoe() {
+        let mut deps = init_with_balance();
+
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+
+        let res: ShoeResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetShoe {
+                    address: "user0000".to_string(),
+                    token: testing::token("token0000"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(6, res.shoe.decks);
+        assert_eq!(3, res.dealt);
+        assert_eq!(6 * 52 - 3, res.remaining);
+
+        // cards dealt by an action are removed from the shoe
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Stand,
+            seed: Some(seed("user0000")),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+
+        let game: GameStateResponce = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetGameState {
+                    token: testing::token("token0000"),
+                    address: "user0000".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let res: ShoeResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetShoe {
+                    address: "user0000".to_string(),
+                    token: testing::token("token0000"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            (game.state.dealer_hand.len() + 2) as u32,
+            res.dealt,
+            "dealer hand and two player cards"
+        );
+
+        // reshuffled at the next bet once past the penetration
+        let token = testing::token("token0000");
+        let key = (&Addr::unchecked("user0000"), &token);
+        SHOE.update(&mut deps.storage, key, |shoe| {
+            let mut shoe = shoe.unwrap();
+            shoe.remaining = vec![1; 52];
+            StdResult::Ok(shoe)
+        })
+        .unwrap();
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        let res: ShoeResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetShoe {
+                    address: "user0000".to_string(),
+                    token: testing::token("token0000"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(3, res.dealt);
+
+        // invalid shoe config
+        let mut deps = mock_dependencies(&[]);
+        let msg = InstantiateMsg {
+            decks: Some(9),
+            ..instantiate_msg()
+        };
+        let err =
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::InvalidShoe {}, err);
+    }
+
+    #[test]
+    fn withdraw() {
+        let mut deps = init_with_balance();
+        let msg = ExecuteMsg::Withdraw {
+            amount: Uint128::new(100),
+            asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+
+        // other user could not withdraw
+        let msg = ExecuteMsg::Withdraw {
+            amount: Uint128::new(100),
+            asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::NoSuchAccountExists {}, err);
+
+        // insufficient balance
+        let msg = ExecuteMsg::Withdraw {
+            amount: Uint128::new(10000),
+            asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(
+            ContractError::InsufficientBalance {
+                balance: Uint128::new(900)
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn native_deposit() {
+        let mut deps = init_with_balance();
+        let uluna = AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        };
+        let msg = ExecuteMsg::AddToken {
+            token: uluna.clone(),
+            bet_limits: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user0000", &[]),
+            ExecuteMsg::Deposit {},
+        )
+        .unwrap_err();
+        assert_eq!(ContractError::NoFunds {}, err);
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user0000", &coins(500, "uusd")),
+            ExecuteMsg::Deposit {},
+        )
+        .unwrap_err();
+        assert_eq!(
+            ContractError::UnsupportedDenom {
+                denom: "uusd".to_string()
+            },
+            err
+        );
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user0000", &coins(500, "uluna")),
+            ExecuteMsg::Deposit {},
+        )
+        .unwrap();
+        assert_eq!("uluna", res.attributes[1].value);
+        assert_eq!("500", res.attributes[2].value);
+
+        // kept apart from the game token
+        let query_deposit = |deps: &TestDeps, asset| {
+            let msg = QueryMsg::GetDeposit {
+                address: "user0000".to_string(),
+                asset,
+            };
+            let res: DepositResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.deposit
+        };
+        assert_eq!(Uint128::new(500), query_deposit(&deps, uluna.clone()));
+        assert_eq!(
+            Uint128::new(1000),
+            query_deposit(&deps, AssetInfo::cw20(&Addr::unchecked("token0000")))
+        );
+
+        let msg = ExecuteMsg::Withdraw {
+            amount: Uint128::new(200),
+            asset: uluna.clone(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "user0000".to_string(),
+                amount: coins(200, "uluna"),
+            })],
+            res.messages
+        );
+        assert_eq!(Uint128::new(300), query_deposit(&deps, uluna.clone()));
+        assert_eq!(
+            Uint128::new(1000),
+            query_deposit(&deps, AssetInfo::cw20(&Addr::unchecked("token0000")))
+        );
+
+        let msg = ExecuteMsg::Withdraw {
+            amount: Uint128::new(400),
+            asset: uluna,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(
+            ContractError::InsufficientBalance {
+                balance: Uint128::new(300)
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn native_bet() {
+        let mut deps = init_with_balance();
+        let uluna = AssetInfo::native("uluna");
+        // a cw20 whose address reads like the denom
+        let cw20_uluna = testing::token("uluna");
+        for token in [uluna.clone(), cw20_uluna.clone()] {
+            let msg = ExecuteMsg::AddToken {
+                token,
+                bet_limits: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        }
+
+        // a single whitelisted denom at a time
+        let provide = |deps: &mut TestDeps, funds: &[Coin]| {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("creator", funds),
+                ExecuteMsg::ProvideLiquidity {},
+            )
+        };
+        let err = provide(&mut deps, &[]).unwrap_err();
+        assert_eq!(ContractError::NoFunds {}, err);
+        let err = provide(
+            &mut deps,
+            &[Coin::new(10_000, "uluna"), Coin::new(10_000, "uusd")],
+        )
+        .unwrap_err();
+        assert_eq!(ContractError::MultipleDenoms {}, err);
+        let err = provide(&mut deps, &coins(10_000, "uusd")).unwrap_err();
+        assert_eq!(
+            ContractError::UnsupportedDenom {
+                denom: "uusd".to_string()
+            },
+            err
+        );
+        provide(&mut deps, &coins(10_000, "uluna")).unwrap();
+
+        let msg = hook("user0000", 700, &Cw20HookMsg::Deposit {});
+        execute(deps.as_mut(), mock_env(), mock_info("uluna", &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user0000", &coins(500, "uluna")),
+            ExecuteMsg::Deposit {},
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::Bet {
+            token: uluna.clone(),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        let game = GAMESTATE
+            .load(&deps.storage, (&Addr::unchecked("user0000"), &uluna))
+            .unwrap();
+        assert_eq!(Uint128::new(100), game.total_bet_amount);
+        if game.ingame {
+            let vault = VAULT
+                .load(&deps.storage, (&Addr::unchecked("user0000"), &uluna))
+                .unwrap();
+            assert_eq!(Uint128::new(400), vault.balance);
+        }
+
+        // the cw20 keeps its own vault, bankroll and games
+        let key = (&Addr::unchecked("user0000"), &cw20_uluna);
+        assert_eq!(
+            Uint128::new(700),
+            VAULT.load(&deps.storage, key).unwrap().balance
+        );
+        assert!(!GAMESTATE.has(&deps.storage, key));
+        assert_eq!(
+            Bankroll::default(),
+            BANKROLL.load(&deps.storage, &cw20_uluna).unwrap()
+        );
+        let bankroll = BANKROLL.load(&deps.storage, &uluna).unwrap();
+        assert_eq!(Uint128::new(10_000), bankroll.total_shares);
+    }
+
+    #[test]
+    fn multi_token() {
+        let mut deps = init_with_balance();
+        let bet = |token: &str, amount: u128| ExecuteMsg::Bet {
+            token: testing::token(token),
+            amount: Uint128::new(amount),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        let bankroll = |deps: &TestDeps, token: &str| {
+            let msg = QueryMsg::GetBankroll {
+                token: testing::token(token),
+            };
+            let res: BankrollResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res
+        };
+
+        // only whitelisted tokens
+        let msg = hook("user0000", 500, &Cw20HookMsg::Deposit {});
+        let err = execute(deps.as_mut(), mock_env(), mock_info("token0001", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+
+        let msg = ExecuteMsg::AddToken {
+            token: testing::token("token0001"),
+            bet_limits: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user0000", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let res: TokensResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetTokens {}).unwrap())
+                .unwrap();
+        let tokens: Vec<_> = res.tokens.into_iter().map(|t| t.token).collect();
+        assert_eq!(
+            vec![testing::token("token0000"), testing::token("token0001")],
+            tokens
+        );
+
+        // the new token has its own bankroll
+        let msg = hook("user0000", 500, &Cw20HookMsg::Deposit {});
+        execute(deps.as_mut(), mock_env(), mock_info("token0001", &[]), msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user0000", &[]),
+            bet("token0001", 100),
+        )
+        .unwrap_err();
+        assert_eq!(ContractError::InsufficientBankroll {}, err);
+
+        let msg = hook("creator", 10_000, &Cw20HookMsg::ProvideLiquidity {});
+        execute(deps.as_mut(), mock_env(), mock_info("token0001", &[]), msg).unwrap();
+        assert_eq!(Uint128::new(10_000), bankroll(&deps, "token0001").balance);
+        assert_eq!(
+            Uint128::new(1_000_000),
+            bankroll(&deps, "token0000").balance
+        );
+
+        // a game in each token at the same time
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user0000", &[]),
+            bet("token0000", 100),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user0000", &[]),
+            bet("token0001", 100),
+        )
+        .unwrap();
+        for token in ["token0000", "token0001"] {
+            let msg = QueryMsg::GetDeposit {
+                address: "user0000".to_string(),
+                asset: AssetInfo::cw20(&Addr::unchecked(token)),
+            };
+            let res: DepositResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            let game: GameStateResponce = from_binary(
+                &query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::GetGameState {
+                        address: "user0000".to_string(),
+                        token: testing::token(token),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(Uint128::new(100), game.state.total_bet_amount);
+            // each token deals from its own shoe
+            let shoe: ShoeResponse = from_binary(
+                &query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::GetShoe {
+                        address: "user0000".to_string(),
+                        token: testing::token(token),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(game.state.dealer_hand.len() as u32 + 2, shoe.dealt);
+            if game.state.ingame {
+                let expected = if token == "token0000" { 900 } else { 400 };
+                assert_eq!(Uint128::new(expected), res.deposit);
+            }
+        }
+
+        // delisted, no new bets or liquidity
+        let msg = ExecuteMsg::RemoveToken {
+            token: testing::token("token0001"),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = hook("creator", 10_000, &Cw20HookMsg::ProvideLiquidity {});
+        let err = execute(deps.as_mut(), mock_env(), mock_info("token0001", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+        let game: GameStateResponce = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetGameState {
+                    address: "user0000".to_string(),
+                    token: testing::token("token0001"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        if game.state.ingame {
+            let msg = ExecuteMsg::Action {
+                token: testing::token("token0001"),
+                action: ActionCommand::Stand,
+                seed: Some(seed("user0000")),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user0000", &[]),
+            bet("token0001", 100),
+        )
+        .unwrap_err();
+        assert_eq!(
+            ContractError::TokenNotWhitelisted {
+                token: "token0001".to_string()
+            },
+            err
+        );
+
+        // but the liquidity can still be withdrawn
+        let msg = ExecuteMsg::WithdrawLiquidity {
+            token: testing::token("token0001"),
+            shares: Uint128::new(10_000),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert!(bankroll(&deps, "token0001").balance.is_zero());
+
+        // and the round counts in its own stats
+        let msg = QueryMsg::GetStats {
+            token: testing::token("token0001"),
+        };
+        let res: StatsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(1, res.stats.rounds);
+        assert_eq!(Uint128::new(100), res.stats.wagered);
+    }
+
+    #[test]
+    fn update_config() {
+        let mut deps = init_with_balance();
+
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            insurance: Some(true),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            decks: Some(9),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::InvalidShoe {}, err);
+
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            insurance: Some(true),
+            decks: Some(2),
+            ..Default::default()
+        });
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let res: ConfigResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap())
+                .unwrap();
+        assert!(res.config.insurance);
+        assert_eq!(2, res.config.decks);
+        assert_eq!(PayoutTable::default(), res.config.payout_table);
+    }
+
+    #[test]
+    fn ownership_transfer() {
+        let mut deps = init_with_balance();
+
+        let msg = ExecuteMsg::ProposeNewOwner {
+            owner: "owner0000".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+
+        // nothing proposed yet
+        let msg = ExecuteMsg::AcceptOwnership {};
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+
+        let msg = ExecuteMsg::ProposeNewOwner {
+            owner: "owner0000".to_string(),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let res: StateResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetState {}).unwrap()).unwrap();
+        assert_eq!("creator", res.owner);
+        assert_eq!(Some("owner0000".to_string()), res.pending_owner);
+
+        // only the proposed owner can accept
+        let msg = ExecuteMsg::AcceptOwnership {};
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+
+        let msg = ExecuteMsg::AcceptOwnership {};
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        let res: StateResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetState {}).unwrap()).unwrap();
+        assert_eq!("owner0000", res.owner);
+        assert_eq!(None, res.pending_owner);
+
+        // the old owner is locked out
+        let msg = ExecuteMsg::Pause {};
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+        let msg = ExecuteMsg::Pause {};
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn pause() {
+        let mut deps = init_with_balance();
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::Pause {};
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+        let msg = ExecuteMsg::Pause {};
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let res: StateResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetState {}).unwrap()).unwrap();
+        assert!(res.paused);
+
+        // the game in progress can be finished
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Stand,
+            seed: Some(seed("user0000")),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Paused {}, err);
+
+        // withdrawals still work
+        let msg = ExecuteMsg::Withdraw {
+            amount: Uint128::new(100),
+            asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::Unpause {};
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+        let msg = ExecuteMsg::Unpause {};
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn migrate_version_check() {
+        let mut deps = init_with_balance();
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(CONTRACT_VERSION, res.attributes[2].value);
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            ContractError::CannotDowngrade {
+                version: "99.0.0".to_string()
+            },
+            err
+        );
+
+        set_contract_version(deps.as_mut().storage, "crates.io:other", CONTRACT_VERSION).unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            ContractError::CannotMigrate {
+                contract: "crates.io:other".to_string()
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn force_settle() {
+        let mut deps = init_with_balance();
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+
+        let query_msg = QueryMsg::GetGameState {
+            token: testing::token("token0000"),
+            address: "user0000".to_string(),
+        };
+        let res: GameStateResponce =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap()).unwrap();
+        assert_eq!(mock_env().block.height, res.state.started_height);
+        assert_eq!(Some(DEFAULT_TIMEOUT), res.time_left);
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(DEFAULT_TIMEOUT - 10);
+        let res: GameStateResponce =
+            from_binary(&query(deps.as_ref(), env.clone(), query_msg.clone()).unwrap()).unwrap();
+        assert_eq!(Some(10), res.time_left);
+
+        let msg = ExecuteMsg::ForceSettle {
+            token: testing::token("token0000"),
+            address: "user0000".to_string(),
+        };
+        let err = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::NotTimedOut { seconds_left: 10 }, err);
+
+        // anyone can close the game once timed out, the hand stands
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(DEFAULT_TIMEOUT);
+        let msg = ExecuteMsg::ForceSettle {
+            token: testing::token("token0000"),
+            address: "user0000".to_string(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!("force_settle", res.attributes[0].value);
+        assert_eq!("user0000", res.attributes[1].value);
+        assert_eq!("end", res.attributes[2].value);
+        let res: GameStateResponce =
+            from_binary(&query(deps.as_ref(), env.clone(), query_msg.clone()).unwrap()).unwrap();
+        assert!(!res.state.ingame);
+        assert_eq!(None, res.time_left);
+
+        let msg = ExecuteMsg::ForceSettle {
+            token: testing::token("token0000"),
+            address: "user0000".to_string(),
+        };
+        let err = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::ActionBeforeBet {}, err);
+
+        // forfeit loses the bet
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            timeout: Some(0),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::InvalidTimeout {}, err);
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            timeout: Some(60),
+            timeout_action: Some(TimeoutAction::Forfeit),
+            ..Default::default()
+        });
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let deposit = |deps: &TestDeps| {
+            let msg = QueryMsg::GetDeposit {
+                address: "user0000".to_string(),
+                asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+            };
+            let res: DepositResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.deposit
+        };
+        let before = deposit(&deps);
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let msg = ExecuteMsg::ForceSettle {
+            token: testing::token("token0000"),
+            address: "user0000".to_string(),
+        };
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!("Loose", res.attributes[3].value);
+        assert!(res.attributes[5].value.starts_with("Forfeit"));
+        assert_eq!(before - Uint128::new(100), deposit(&deps));
+    }
+
+    #[test]
+    fn history() {
+        let mut deps = init_with_balance();
+
+        let mut payouts = vec![];
+        for round_id in 1..=3u64 {
+            let msg = ExecuteMsg::Bet {
+                token: testing::token("token0000"),
+                amount: Uint128::new(100),
+                seed_commitment: seed_commitment("user0000"),
+                perfect_pairs: None,
+                twenty_one_plus_three: None,
+            };
+            let mut res =
+                execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+            if !res.attributes.iter().any(|a| a.key == "round_id") {
+                let msg = ExecuteMsg::Action {
+                    token: testing::token("token0000"),
+                    action: ActionCommand::Stand,
+                    seed: Some(seed("user0000")),
+                };
+                res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+            }
+            let attr = |key: &str| {
+                res.attributes
+                    .iter()
+                    .find(|a| a.key == key)
+                    .map(|a| a.value.clone())
+                    .unwrap()
+            };
+            assert_eq!(round_id.to_string(), attr("round_id"));
+            payouts.push(attr("balance_change"));
+        }
+
+        let history = |deps: &TestDeps, address: &str, start_after, limit| {
+            let msg = QueryMsg::GetHistory {
+                address: address.to_string(),
+                start_after,
+                limit,
+            };
+            let res: HistoryResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.rounds
+        };
+
+        // newest first
+        let rounds = history(&deps, "user0000", None, Some(2));
+        assert_eq!(
+            vec![3, 2],
+            rounds.iter().map(|r| r.round_id).collect::<Vec<_>>()
+        );
+        let round = &rounds[0];
+        assert_eq!(Uint128::new(100), round.bet);
+        assert_eq!(Uint128::new(0), round.side_bets);
+        assert_eq!(payouts[2], round.payout.to_string());
+        assert_eq!(mock_env().block.height, round.height);
+        assert_eq!(1, round.player_hands.len());
+        assert_eq!(1, round.results.len());
+        assert!(round.dealer_hand.len() >= 2);
+
+        let rounds = history(&deps, "user0000", Some(2), None);
+        assert_eq!(1, rounds.len());
+        assert_eq!(1, rounds[0].round_id);
+        assert_eq!(payouts[0], rounds[0].payout.to_string());
+
+        assert!(history(&deps, "other0000", None, None).is_empty());
+    }
+
+    #[test]
+    fn stats_and_leaderboard() {
+        let mut deps = init_with_balance();
+        testing::deposit(&mut deps, "user0001", 1000);
+
+        // bet 100 and stand, returns the payout
+        let play = |deps: &mut TestDeps, player: &str| {
+            let msg = ExecuteMsg::Bet {
+                token: testing::token("token0000"),
+                amount: Uint128::new(100),
+                seed_commitment: seed_commitment("user0000"),
+                perfect_pairs: None,
+                twenty_one_plus_three: None,
+            };
+            let mut res = execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg).unwrap();
+            if !res.attributes.iter().any(|a| a.key == "round_id") {
+                let msg = ExecuteMsg::Action {
+                    token: testing::token("token0000"),
+                    action: ActionCommand::Stand,
+                    seed: Some(seed("user0000")),
+                };
+                res = execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg).unwrap();
+            }
+            let payout = res
+                .attributes
+                .iter()
+                .find(|a| a.key == "balance_change")
+                .unwrap()
+                .value
+                .parse::<u128>()
+                .unwrap();
+            payout
+        };
+        let payouts = [
+            play(&mut deps, "user0000") + play(&mut deps, "user0000"),
+            play(&mut deps, "user0001"),
+        ];
+
+        let res: StatsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetStats {
+                    token: testing::token("token0000"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let stats = res.stats;
+        assert_eq!(3, stats.rounds);
+        assert_eq!(Uint128::new(300), stats.wagered);
+        assert_eq!(Uint128::new(payouts[0] + payouts[1]), stats.paid_out);
+        let results = &stats.results;
+        assert_eq!(
+            3,
+            results.blackjack + results.win + results.loose + results.draw + results.surrender
+        );
+        let judges = &stats.judges;
+        assert_eq!(
+            3,
+            judges.dealer_busted
+                + judges.player_busted
+                + judges.dealer_win
+                + judges.player_win
+                + judges.player_bj_win
+                + judges.draw
+        );
+        let edge = if stats.paid_out <= stats.wagered {
+            Decimal::from_ratio(stats.wagered - stats.paid_out, stats.wagered).to_string()
+        } else {
+            format!(
+                "-{}",
+                Decimal::from_ratio(stats.paid_out - stats.wagered, stats.wagered)
+            )
+        };
+        assert_eq!(edge, res.house_edge);
+
+        let leaderboard = |deps: &TestDeps, rank_by, start_after: Option<&str>| {
+            let msg = QueryMsg::GetLeaderboard {
+                token: testing::token("token0000"),
+                rank_by,
+                start_after: start_after.map(|a| a.to_string()),
+                limit: Some(1),
+            };
+            let res: LeaderboardResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.entries
+        };
+
+        let entries = leaderboard(&deps, RankBy::Volume, None);
+        assert_eq!(
+            vec![LeaderboardEntry {
+                address: "user0000".to_string(),
+                rounds: 2,
+                wagered: Uint128::new(200),
+                paid_out: Uint128::new(payouts[0]),
+                profit: (payouts[0] as i128 - 200).to_string(),
+            }],
+            entries
+        );
+        let entries = leaderboard(&deps, RankBy::Volume, Some("user0000"));
+        assert_eq!("user0001", entries[0].address);
+        assert_eq!((payouts[1] as i128 - 100).to_string(), entries[0].profit);
+        assert!(leaderboard(&deps, RankBy::Volume, Some("user0001")).is_empty());
+
+        let profits = [payouts[0] as i128 - 200, payouts[1] as i128 - 100];
+        let (first, second) = if profits[0] >= profits[1] {
+            ("user0000", "user0001")
+        } else {
+            ("user0001", "user0000")
+        };
+        let entries = leaderboard(&deps, RankBy::Profit, None);
+        assert_eq!(first, entries[0].address);
+        let entries = leaderboard(&deps, RankBy::Profit, Some(first));
+        assert_eq!(second, entries[0].address);
+    }
+
+    #[test]
+    fn hook_bet() {
+        let mut deps = init_with_balance();
+        let hook = |amount: u128, auto_withdraw| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "user0001".to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(&Cw20HookMsg::Bet {
+                    amount: None,
+                    seed_commitment: seed_commitment("user0000"),
+                    perfect_pairs: None,
+                    twenty_one_plus_three: None,
+                    auto_withdraw,
+                })
+                .unwrap(),
+            })
+        };
+        let deposit = |deps: &TestDeps| {
+            let msg = QueryMsg::GetDeposit {
+                address: "user0001".to_string(),
+                asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+            };
+            let res: DepositResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.deposit
+        };
+        // bet through the hook and stand, returns the settling response and the payout
+        let play = |deps: &mut TestDeps, auto_withdraw| {
+            let mut res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("token0000", &[]),
+                hook(100, auto_withdraw),
+            )
+            .unwrap();
+            assert_eq!("bet", res.attributes[0].value);
+            assert_eq!("100", res.attributes[1].value);
+            if !res.attributes.iter().any(|a| a.key == "round_id") {
+                let msg = ExecuteMsg::Action {
+                    token: testing::token("token0000"),
+                    action: ActionCommand::Stand,
+                    seed: Some(seed("user0000")),
+                };
+                res = execute(deps.as_mut(), mock_env(), mock_info("user0001", &[]), msg).unwrap();
+            }
+            let payout = res
+                .attributes
+                .iter()
+                .find(|a| a.key == "balance_change")
+                .unwrap()
+                .value
+                .parse::<u128>()
+                .unwrap();
+            (res, Uint128::new(payout))
+        };
+
+        // only the configured token
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other0000", &[]),
+            hook(100, None),
+        )
+        .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+
+        // deposit and bet in one go, the payout goes to the vault
+        let (res, payout) = play(&mut deps, None);
+        assert!(res.messages.is_empty());
+        assert_eq!(payout, deposit(&deps));
+
+        // the payout is sent back with auto withdraw
+        let before = deposit(&deps);
+        let (res, payout) = play(&mut deps, Some(true));
+        assert_eq!(before, deposit(&deps));
+        if payout.is_zero() {
+            assert!(res.messages.is_empty());
+        } else {
+            assert_eq!(
+                vec![SubMsg::new(WasmMsg::Execute {
+                    contract_addr: "token0000".to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: "user0001".to_string(),
+                        amount: payout,
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                })],
+                res.messages
+            );
+        }
     }
 }
@@ -12,6 +12,30 @@ pub enum ContractError {
     #[error("Invalid State")]
     InvalidState {},
 
+    #[error("New bets are paused")]
+    Paused {},
+
+    #[error("Cannot migrate from contract {contract}")]
+    CannotMigrate { contract: String },
+
+    #[error("Cannot migrate from newer version {version}")]
+    CannotDowngrade { version: String },
+
+    #[error("Invalid version {version}")]
+    InvalidVersion { version: String },
+
+    #[error("No funds sent")]
+    NoFunds {},
+
+    #[error("Token {token} is not whitelisted")]
+    TokenNotWhitelisted { token: String },
+
+    #[error("Denom {denom} is not accepted")]
+    UnsupportedDenom { denom: String },
+
+    #[error("Only one denom can be sent")]
+    MultipleDenoms {},
+
     #[error("No such account exists")]
     NoSuchAccountExists {},
 
@@ -21,6 +45,18 @@ pub enum ContractError {
     #[error("Bet amount must be >0")]
     BetAmountZero {},
 
+    #[error("Bet is below the table minimum")]
+    BetTooSmall { min_bet: Uint128 },
+
+    #[error("Bet is above the table maximum")]
+    BetTooLarge { max_bet: Uint128 },
+
+    #[error("Bet is above the share of the bankroll allowed for one bet")]
+    BetExceedsBankrollLimit { max_bet: Uint128 },
+
+    #[error("Invalid bet limits")]
+    InvalidBetLimits {},
+
     #[error("Insufficient balance")]
     InsufficientBalance { balance: Uint128 },
 
@@ -35,6 +71,126 @@ pub enum ContractError {
 
     #[error("DoubleDown is not allowed")]
     DoubleDownNotAllowed,
+
+    #[error("Wrong split amount")]
+    WrongSplitAmount { amount: Uint128 },
+
+    #[error("Split is not allowed")]
+    SplitNotAllowed {},
+
+    #[error("Insurance is not allowed")]
+    InsuranceNotAllowed {},
+
+    #[error("Wrong insurance amount")]
+    WrongInsuranceAmount { max: Uint128 },
+
+    #[error("Surrender is not allowed")]
+    SurrenderNotAllowed {},
+
+    #[error("Invalid payout table")]
+    InvalidPayoutTable {},
+
+    #[error("Invalid shoe, decks must be 1 to 8 and penetration up to 1")]
+    InvalidShoe {},
+
+    #[error("Timeout must be >0")]
+    InvalidTimeout {},
+
+    #[error("Game has not timed out yet")]
+    NotTimedOut { seconds_left: u64 },
+
+    #[error("Seed commitment must be a sha256 hash")]
+    InvalidCommitment {},
+
+    #[error("Seed must be revealed on the first action")]
+    SeedNotRevealed {},
+
+    #[error("Revealed seed does not match the commitment")]
+    SeedMismatch {},
+
+    #[error("The house has not committed to its seeds")]
+    NoHouseChain {},
+
+    #[error("Waiting for the house seed of the last move")]
+    SeedPending {},
+
+    #[error("No move waits on house seed {index}")]
+    SeedNotWaitedOn { index: u64 },
+
+    #[error("House seed {index} follows one not revealed yet")]
+    SeedOutOfOrder { index: u64 },
+
+    #[error("Too many moves wait on the next house seed")]
+    HouseBusy {},
+
+    #[error("House seed does not hash to the one before it")]
+    HouseSeedMismatch {},
+
+    #[error("Bet exceeds what the house bankroll can cover")]
+    InsufficientBankroll {},
+
+    #[error("Insufficient LP shares")]
+    InsufficientShares { shares: Uint128 },
+
+    #[error("Liquidity too small to mint a share")]
+    LiquidityTooSmall {},
+
+    #[error("Liquidity is reserved for games in progress")]
+    LiquidityReserved { available: Uint128 },
+
+    #[error("Table has no free seat")]
+    TableFull {},
+
+    #[error("Already seated at the table")]
+    AlreadySeated {},
+
+    #[error("Not seated at the table")]
+    NotSeated {},
+
+    #[error("Bet already placed for this round")]
+    AlreadyBet {},
+
+    #[error("Betting is closed")]
+    BettingClosed {},
+
+    #[error("Betting is still open")]
+    BettingOpen { seconds_left: u64 },
+
+    #[error("Another seat is to act")]
+    NotYourTurn {},
+
+    #[error("Action is not offered at tables")]
+    ActionNotOffered {},
+
+    #[error("Round in play at the table")]
+    TableInPlay {},
+
+    #[error("Invalid tournament")]
+    InvalidTournament {},
+
+    #[error("Tournament is full")]
+    TournamentFull {},
+
+    #[error("Registration is closed")]
+    RegistrationClosed {},
+
+    #[error("Already registered")]
+    AlreadyRegistered {},
+
+    #[error("Not registered")]
+    NotRegistered {},
+
+    #[error("Tournament has not started yet")]
+    TournamentNotStarted {},
+
+    #[error("Tournament is over")]
+    TournamentOver {},
+
+    #[error("Tournament is still running")]
+    TournamentNotOver {},
+
+    #[error("Insufficient chips")]
+    InsufficientChips { chips: Uint128 },
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
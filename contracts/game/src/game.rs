@@ -1,57 +1,93 @@
 use std::fmt::Display;
 
-use rand::prelude::SliceRandom;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-use crate::card::{BJCard, Hand, CARDLIST};
+use crate::card::{BJCard, Hand, Rank};
+use crate::shoe::Shoe;
 
-pub(crate) fn draw_one<T: rand::Rng>(rng: &mut T) -> BJCard {
-    CARDLIST
-        .choose(rng)
-        .expect("something went wrong")
-        .to_owned()
+pub(crate) fn draw_one<T: rand::Rng>(shoe: &mut Shoe, rng: &mut T) -> BJCard {
+    shoe.draw(rng)
 }
 
-pub(crate) fn dealer_action<T: rand::Rng>(hand: &[BJCard], rng: &mut T) -> Vec<BJCard> {
+/// Dealer draws to 17. With `hits_soft_17` the dealer also draws on soft 17 (H17).
+pub(crate) fn dealer_action<T: rand::Rng>(
+    hand: &[BJCard],
+    hits_soft_17: bool,
+    shoe: &mut Shoe,
+    rng: &mut T,
+) -> Vec<BJCard> {
     let mut new_hand = Vec::from(hand);
-    let mut score = calc_score(hand);
+    let mut score = calc_soft_score(hand);
 
-    while score < 17 {
-        new_hand.push(draw_one(rng));
-        score = calc_score(&new_hand);
+    while score.total < 17 || (hits_soft_17 && score.total == 17 && score.soft) {
+        new_hand.push(draw_one(shoe, rng));
+        score = calc_soft_score(&new_hand);
     }
 
     new_hand
 }
 
-pub(crate) fn calc_score(hand: &[BJCard]) -> i32 {
+/// Hand total. `soft` when an ace is counted as 11.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct Score {
+    pub total: i32,
+    pub soft: bool,
+}
+
+impl Display for Score {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            if self.soft { "soft" } else { "hard" },
+            self.total
+        )
+    }
+}
+
+pub(crate) fn calc_soft_score(hand: &[BJCard]) -> Score {
     let mut sum = 0;
-    let mut hand = hand.to_owned();
-    hand.sort();
     for card in hand.iter() {
-        let score = match card {
-            BJCard::Two => 2,
-            BJCard::Three => 3,
-            BJCard::Four => 4,
-            BJCard::Five => 5,
-            BJCard::Six => 6,
-            BJCard::Seven => 7,
-            BJCard::Eight => 8,
-            BJCard::Nine => 9,
-            BJCard::Ten | BJCard::Jack | BJCard::Queeen | BJCard::King => 10,
-            BJCard::Ace if sum + 11 <= 21 => 11,
-            BJCard::Ace => 1,
+        sum += match card.rank {
+            Rank::Two => 2,
+            Rank::Three => 3,
+            Rank::Four => 4,
+            Rank::Five => 5,
+            Rank::Six => 6,
+            Rank::Seven => 7,
+            Rank::Eight => 8,
+            Rank::Nine => 9,
+            Rank::Ten | Rank::Jack | Rank::Queen | Rank::King => 10,
+            Rank::Ace => 1,
         };
+    }
 
-        sum += score;
+    // at most one ace can count as 11
+    if hand.iter().any(|c| c.rank == Rank::Ace) && sum + 10 <= 21 {
+        Score {
+            total: sum + 10,
+            soft: true,
+        }
+    } else {
+        Score {
+            total: sum,
+            soft: false,
+        }
     }
-    sum
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub(crate) enum GameResult {
+pub(crate) fn calc_score(hand: &[BJCard]) -> i32 {
+    calc_soft_score(hand).total
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub enum GameResult {
+    Blackjack,
     Win,
     Loose,
     Draw,
+    Surrender,
 }
 
 impl Display for GameResult {
@@ -60,22 +96,46 @@ impl Display for GameResult {
             f,
             "{}",
             match self {
+                GameResult::Blackjack => "Blackjack",
                 GameResult::Win => "Win",
                 GameResult::Loose => "Loose",
                 GameResult::Draw => "Draw",
+                GameResult::Surrender => "Surrender",
             }
         )
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub(crate) enum Judge {
+impl From<Judge> for GameResult {
+    fn from(judge: Judge) -> Self {
+        match judge {
+            Judge::DealerBusted(_) => GameResult::Win,
+            Judge::PlayerBusted(_) => GameResult::Loose,
+            Judge::DealerWin(_, _) => GameResult::Loose,
+            Judge::PlayerWin(_, _) => GameResult::Win,
+            Judge::PlayerBJWin(_, _) => GameResult::Blackjack,
+            Judge::Draw(_, _) => GameResult::Draw,
+            Judge::Surrender(_) => GameResult::Surrender,
+            Judge::Forfeit(_) => GameResult::Loose,
+            Judge::HouseTimeout(_) => GameResult::Win,
+        }
+    }
+}
+
+/// How a hand was decided, with the dealer and player totals.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Judge {
     DealerBusted(i32),
     PlayerBusted(i32),
     DealerWin(i32, i32),
     PlayerWin(i32, i32),
     PlayerBJWin(i32, i32),
     Draw(i32, i32),
+    Surrender(i32),
+    Forfeit(i32),
+    /// the house did not reveal its seed in time and paid all it reserved for the round
+    HouseTimeout(i32),
 }
 
 impl Display for Judge {
@@ -87,6 +147,9 @@ impl Display for Judge {
             Judge::PlayerWin(d, p) => write!(f, "PlayerWin {} {}", d, p),
             Judge::PlayerBJWin(d, p) => write!(f, "PlayerBJWin {} {}", d, p),
             Judge::Draw(d, p) => write!(f, "Draw {} {}", d, p),
+            Judge::Surrender(p) => write!(f, "Surrender {}", p),
+            Judge::Forfeit(p) => write!(f, "Forfeit {}", p),
+            Judge::HouseTimeout(p) => write!(f, "HouseTimeout {}", p),
         }
     }
 }
@@ -114,136 +177,556 @@ pub(crate) fn judge(dealer: &[BJCard], player: &[BJCard]) -> Judge {
     }
 }
 
-pub(crate) fn first_deal<T: rand::Rng>(rng: &mut T) -> (Hand, Hand) {
-    let dealer = draw_one(rng);
-    let player1 = draw_one(rng);
-    let player2 = draw_one(rng);
+pub(crate) fn is_blackjack(hand: &[BJCard]) -> bool {
+    hand.len() == 2 && calc_score(hand) == 21
+}
+
+/// Whether the dealer's first two cards make blackjack, regardless of cards drawn after.
+pub(crate) fn dealer_blackjack(dealer: &[BJCard]) -> bool {
+    dealer.len() >= 2 && is_blackjack(&dealer[..2])
+}
+
+/// The dealer checks the hole card for blackjack when showing an ace or a ten.
+pub(crate) fn dealer_peeks(dealer: &[BJCard]) -> bool {
+    dealer.len() == 1 && calc_score(dealer) >= 10
+}
+
+/// Whether the peek finds dealer blackjack, none when the dealer does not peek.
+/// The hole card is only drawn when the dealer plays, so the peek looks at the card the shoe
+/// would give now. See [`draw_hole`].
+pub(crate) fn peek_blackjack<T: rand::Rng>(
+    dealer: &[BJCard],
+    shoe: &Shoe,
+    rng: &mut T,
+) -> Option<bool> {
+    if dealer_peeks(dealer) {
+        Some(is_blackjack(&[dealer[0], shoe.peek(rng)]))
+    } else {
+        None
+    }
+}
+
+/// Draw the hole card under the up card, matching what the peek found if the dealer peeked.
+pub(crate) fn draw_hole<T: rand::Rng>(
+    up: BJCard,
+    blackjack: Option<bool>,
+    shoe: &mut Shoe,
+    rng: &mut T,
+) -> BJCard {
+    match blackjack {
+        Some(blackjack) => shoe.draw_where(rng, |hole| is_blackjack(&[up, *hole]) == blackjack),
+        None => draw_one(shoe, rng),
+    }
+}
+
+/// A hand judged after a split. Two card 21 is not a natural blackjack here.
+pub(crate) fn judge_split(dealer: &[BJCard], player: &[BJCard]) -> Judge {
+    match judge(dealer, player) {
+        Judge::Draw(21, 21) if dealer.len() == 2 && player.len() == 2 => Judge::DealerWin(21, 21),
+        Judge::PlayerBJWin(d, _) if d > 21 => Judge::DealerBusted(d),
+        Judge::PlayerBJWin(d, p) if d == p => Judge::Draw(d, p),
+        Judge::PlayerBJWin(d, p) => Judge::PlayerWin(d, p),
+        judge => judge,
+    }
+}
+
+pub(crate) fn can_split(hand: &[BJCard]) -> bool {
+    matches!(hand, [first, second] if first.rank == second.rank)
+}
+
+/// Perfect Pairs side bet result.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum PairResult {
+    NoPair,
+    Mixed,
+    Colored,
+    Perfect,
+}
+
+impl Display for PairResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PairResult::NoPair => "NoPair",
+                PairResult::Mixed => "MixedPair",
+                PairResult::Colored => "ColoredPair",
+                PairResult::Perfect => "PerfectPair",
+            }
+        )
+    }
+}
+
+/// Judge the player's first two cards for the Perfect Pairs side bet.
+pub(crate) fn judge_perfect_pairs(hand: &[BJCard]) -> PairResult {
+    match hand {
+        [first, second, ..] if first.rank == second.rank => {
+            if first.suit == second.suit {
+                PairResult::Perfect
+            } else if first.suit.is_red() == second.suit.is_red() {
+                PairResult::Colored
+            } else {
+                PairResult::Mixed
+            }
+        }
+        _ => PairResult::NoPair,
+    }
+}
+
+/// 21+3 side bet result, a three card poker hand.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum PokerJudge {
+    Nothing,
+    Flush,
+    Straight,
+    ThreeOfAKind,
+    StraightFlush,
+    SuitedTrips,
+}
+
+impl Display for PokerJudge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PokerJudge::Nothing => "Nothing",
+                PokerJudge::Flush => "Flush",
+                PokerJudge::Straight => "Straight",
+                PokerJudge::ThreeOfAKind => "ThreeOfAKind",
+                PokerJudge::StraightFlush => "StraightFlush",
+                PokerJudge::SuitedTrips => "SuitedTrips",
+            }
+        )
+    }
+}
+
+/// Judge the player's first two cards and the dealer's up card for the 21+3 side bet.
+/// An ace makes a straight both high (Q-K-A) and low (A-2-3).
+pub(crate) fn judge_twenty_one_plus_three(player: &[BJCard], dealer: &[BJCard]) -> PokerJudge {
+    let cards = match (player, dealer) {
+        ([p1, p2, ..], [d, ..]) => [*p1, *p2, *d],
+        _ => return PokerJudge::Nothing,
+    };
+
+    let mut ranks: Vec<u8> = cards.iter().map(|c| c.rank as u8).collect();
+    ranks.sort_unstable();
+    let flush = cards.iter().all(|c| c.suit == cards[0].suit);
+    let trips = ranks[0] == ranks[2];
+    let straight = (ranks[1] == ranks[0] + 1 && ranks[2] == ranks[1] + 1)
+        || ranks == [Rank::Two as u8, Rank::Three as u8, Rank::Ace as u8];
+
+    match (trips, straight, flush) {
+        (true, _, true) => PokerJudge::SuitedTrips,
+        (_, true, true) => PokerJudge::StraightFlush,
+        (true, _, _) => PokerJudge::ThreeOfAKind,
+        (_, true, _) => PokerJudge::Straight,
+        (_, _, true) => PokerJudge::Flush,
+        _ => PokerJudge::Nothing,
+    }
+}
+
+/// Deal the dealer's up card and two player cards. The hole card waits for the dealer to play.
+pub(crate) fn first_deal<T: rand::Rng>(shoe: &mut Shoe, rng: &mut T) -> (Hand, Hand) {
+    let dealer = draw_one(shoe, rng);
+    let player1 = draw_one(shoe, rng);
+    let player2 = draw_one(shoe, rng);
 
     (vec![dealer], vec![player1, player2])
 }
 
+/// Deal a table: one card to each seat and the dealer's up card, then a second card to each seat.
+pub(crate) fn table_deal<T: rand::Rng>(
+    shoe: &mut Shoe,
+    rng: &mut T,
+    seats: usize,
+) -> (Hand, Vec<Hand>) {
+    let mut hands: Vec<Hand> = (0..seats).map(|_| vec![draw_one(shoe, rng)]).collect();
+    let dealer = draw_one(shoe, rng);
+    for hand in hands.iter_mut() {
+        hand.push(draw_one(shoe, rng));
+    }
+
+    (vec![dealer], hands)
+}
+
 #[cfg(test)]
 mod tests {
     use rand::{prelude::SmallRng, SeedableRng};
 
     use super::*;
+    use crate::card::{hand, Suit, CARDLIST};
 
     #[test]
     fn test_draw_one() {
         let mut rng = SmallRng::seed_from_u64(0_u64);
-        let card = draw_one(&mut rng);
+        let mut shoe = Shoe::new(1);
+        let card = draw_one(&mut shoe, &mut rng);
 
-        assert_eq!(BJCard::Seven, card);
+        assert_eq!(BJCard::new(Rank::Queen, Suit::Hearts), card);
     }
 
     #[test]
     fn test_calc_score() {
-        let hand = vec![BJCard::Two];
-        assert_eq!(2, calc_score(&hand));
+        use Rank::*;
+
+        let cards = hand(&[Two]);
+        assert_eq!(2, calc_score(&cards));
 
-        let hand = vec![BJCard::Three];
-        assert_eq!(3, calc_score(&hand));
+        let cards = hand(&[Three]);
+        assert_eq!(3, calc_score(&cards));
 
-        let hand = vec![BJCard::Jack];
-        assert_eq!(10, calc_score(&hand));
+        let cards = hand(&[Jack]);
+        assert_eq!(10, calc_score(&cards));
 
-        let hand = vec![BJCard::Two, BJCard::Two];
-        assert_eq!(4, calc_score(&hand));
+        let cards = hand(&[Two, Two]);
+        assert_eq!(4, calc_score(&cards));
 
-        let hand = vec![BJCard::Ace];
-        assert_eq!(11, calc_score(&hand));
+        let cards = hand(&[Ace]);
+        assert_eq!(11, calc_score(&cards));
 
-        let hand = vec![BJCard::Ace, BJCard::Ace, BJCard::Ace];
-        assert_eq!(13, calc_score(&hand));
+        let cards = hand(&[Ace, Ace, Ace]);
+        assert_eq!(13, calc_score(&cards));
 
-        let hand = vec![BJCard::Ace, BJCard::King];
-        assert_eq!(21, calc_score(&hand));
+        let cards = hand(&[Ace, King]);
+        assert_eq!(21, calc_score(&cards));
 
-        let hand = vec![BJCard::Ace, BJCard::Eight, BJCard::Four];
-        assert_eq!(13, calc_score(&hand));
+        let cards = hand(&[Ace, Eight, Four]);
+        assert_eq!(13, calc_score(&cards));
+
+        let cards = hand(&[Ten, Ace, Ace]);
+        assert_eq!(12, calc_score(&cards));
+    }
+
+    #[test]
+    fn test_calc_soft_score() {
+        use Rank::*;
+
+        let score = calc_soft_score(&hand(&[Ace, Six]));
+        assert_eq!(
+            Score {
+                total: 17,
+                soft: true
+            },
+            score
+        );
+        assert_eq!("soft 17", score.to_string());
+
+        let score = calc_soft_score(&hand(&[Ten, Seven]));
+        assert_eq!(
+            Score {
+                total: 17,
+                soft: false
+            },
+            score
+        );
+        assert_eq!("hard 17", score.to_string());
+
+        let score = calc_soft_score(&hand(&[Ace, Six, Ten]));
+        assert_eq!(
+            Score {
+                total: 17,
+                soft: false
+            },
+            score
+        );
+
+        let score = calc_soft_score(&hand(&[Ace, Ace, Five]));
+        assert_eq!(
+            Score {
+                total: 17,
+                soft: true
+            },
+            score
+        );
     }
 
     #[test]
     fn test_first_deal() {
-        use BJCard::*;
+        use Rank::*;
 
         let mut rng = SmallRng::seed_from_u64(0_u64);
-        let (d, p) = first_deal(&mut rng);
+        let mut shoe = Shoe::new(1);
+        let (d, p) = first_deal(&mut shoe, &mut rng);
+        assert_eq!(49, shoe.len());
+
+        assert_eq!(vec![BJCard::new(Queen, Suit::Hearts)], d);
+        assert_eq!(
+            vec![
+                BJCard::new(Jack, Suit::Hearts),
+                BJCard::new(Ace, Suit::Hearts)
+            ],
+            p
+        );
+    }
+
+    #[test]
+    fn test_table_deal() {
+        use Rank::*;
 
-        assert_eq!(vec![Seven], d);
-        assert_eq!(vec![Ace, Eight], p);
+        // same draws as the first deal, dealt around the table
+        let mut rng = SmallRng::seed_from_u64(0_u64);
+        let mut shoe = Shoe::new(1);
+        let (d, seats) = table_deal(&mut shoe, &mut rng, 1);
+        assert_eq!(vec![BJCard::new(Jack, Suit::Hearts)], d);
+        assert_eq!(
+            vec![vec![
+                BJCard::new(Queen, Suit::Hearts),
+                BJCard::new(Ace, Suit::Hearts)
+            ]],
+            seats
+        );
+
+        let mut shoe = Shoe::new(1);
+        let (d, seats) = table_deal(&mut shoe, &mut rng, 3);
+        assert_eq!(45, shoe.len());
+        assert_eq!(3, seats.len());
+        let mut cards: Vec<BJCard> = seats.concat();
+        cards.extend(d);
+        cards.sort();
+        cards.dedup();
+        assert_eq!(7, cards.len());
     }
 
     #[test]
     fn dealer_hand() {
-        use BJCard::*;
+        use Rank::*;
         let mut rng = SmallRng::seed_from_u64(0_u64);
+        let mut shoe = Shoe::new(6);
 
-        let dealer = vec![Two];
-        let dealer_new = dealer_action(&dealer, &mut rng);
+        let dealer = hand(&[Two]);
+        let dealer_new = dealer_action(&dealer, false, &mut shoe, &mut rng);
 
         assert!(calc_score(&dealer_new) > 16);
 
-        let dealer = vec![Three];
-        let dealer_new = dealer_action(&dealer, &mut rng);
+        let dealer = hand(&[Three]);
+        let dealer_new = dealer_action(&dealer, false, &mut shoe, &mut rng);
+
+        assert!(calc_score(&dealer_new) > 16);
+    }
+
+    #[test]
+    fn dealer_soft_17() {
+        use Rank::*;
+        let mut rng = SmallRng::seed_from_u64(0_u64);
+        let mut shoe = Shoe::new(6);
+
+        // S17
+        let dealer = hand(&[Ace, Six]);
+        assert_eq!(dealer, dealer_action(&dealer, false, &mut shoe, &mut rng));
 
+        // H17
+        let dealer_new = dealer_action(&dealer, true, &mut shoe, &mut rng);
+        assert!(dealer_new.len() > 2);
         assert!(calc_score(&dealer_new) > 16);
+
+        // hard 17 stands either way
+        let dealer = hand(&[Ten, Seven]);
+        assert_eq!(dealer, dealer_action(&dealer, true, &mut shoe, &mut rng));
     }
 
     #[test]
     fn judge_normal() {
-        use BJCard::*;
-        let dealer = vec![Ten, Eight];
-        let player = vec![Ten, Nine];
+        use Rank::*;
+        let dealer = hand(&[Ten, Eight]);
+        let player = hand(&[Ten, Nine]);
         assert_eq!(Judge::PlayerWin(18, 19), judge(&dealer, &player));
 
-        let dealer = vec![Ten, Ten];
-        let player = vec![Ten, Nine];
+        let dealer = hand(&[Ten, Ten]);
+        let player = hand(&[Ten, Nine]);
         assert_eq!(Judge::DealerWin(20, 19), judge(&dealer, &player));
 
-        let dealer = vec![Ten, Ten];
-        let player = vec![Ten, Jack];
+        let dealer = hand(&[Ten, Ten]);
+        let player = hand(&[Ten, Jack]);
         assert_eq!(Judge::Draw(20, 20), judge(&dealer, &player));
     }
 
     #[test]
     fn judge_busted() {
-        use BJCard::*;
+        use Rank::*;
 
-        let dealer = vec![Ten, Five, King];
-        let player = vec![Ten, Jack];
+        let dealer = hand(&[Ten, Five, King]);
+        let player = hand(&[Ten, Jack]);
         assert_eq!(Judge::DealerBusted(25), judge(&dealer, &player));
-        let dealer = vec![Ten, Two, Jack];
-        let player = vec![Ten, Jack];
+        let dealer = hand(&[Ten, Two, Jack]);
+        let player = hand(&[Ten, Jack]);
         assert_eq!(Judge::DealerBusted(22), judge(&dealer, &player));
-        let dealer = vec![Ten, Ten];
-        let player = vec![Ten, Two, Jack];
+        let dealer = hand(&[Ten, Ten]);
+        let player = hand(&[Ten, Two, Jack]);
         assert_eq!(Judge::PlayerBusted(22), judge(&dealer, &player));
     }
 
     #[test]
     fn judge_blackjack() {
-        use BJCard::*;
+        use Rank::*;
 
-        let dealer = vec![Ten, Seven];
-        let player = vec![Ace, Jack];
+        let dealer = hand(&[Ten, Seven]);
+        let player = hand(&[Ace, Jack]);
         assert_eq!(Judge::PlayerBJWin(17, 21), judge(&dealer, &player));
 
-        let dealer = vec![Ten, Five, Six];
-        let player = vec![Ace, Jack];
+        let dealer = hand(&[Ten, Five, Six]);
+        let player = hand(&[Ace, Jack]);
         assert_eq!(Judge::PlayerBJWin(21, 21), judge(&dealer, &player));
     }
 
+    #[test]
+    fn judge_after_split() {
+        use Rank::*;
+
+        let dealer = hand(&[Ten, Seven]);
+        let player = hand(&[Ace, Jack]);
+        assert_eq!(Judge::PlayerWin(17, 21), judge_split(&dealer, &player));
+
+        let dealer = hand(&[Ten, Five, Six]);
+        let player = hand(&[Ace, Jack]);
+        assert_eq!(Judge::Draw(21, 21), judge_split(&dealer, &player));
+
+        let dealer = hand(&[Ten, Ace]);
+        let player = hand(&[Ace, Jack]);
+        assert_eq!(Judge::DealerWin(21, 21), judge_split(&dealer, &player));
+
+        let dealer = hand(&[Ten, Five, Eight]);
+        let player = hand(&[Ace, Jack]);
+        assert_eq!(Judge::DealerBusted(23), judge_split(&dealer, &player));
+
+        let dealer = hand(&[Ten, Eight]);
+        let player = hand(&[Ten, Nine]);
+        assert_eq!(Judge::PlayerWin(18, 19), judge_split(&dealer, &player));
+    }
+
+    #[test]
+    fn split_pair() {
+        use Rank::*;
+
+        assert!(can_split(&hand(&[Eight, Eight])));
+        assert!(can_split(&hand(&[Ace, Ace])));
+        assert!(!can_split(&hand(&[Ten, King])));
+        assert!(!can_split(&hand(&[Eight, Eight, Two])));
+        assert!(!can_split(&hand(&[Eight])));
+    }
+
+    #[test]
+    fn dealer_blackjack_early() {
+        use Rank::*;
+
+        assert!(dealer_blackjack(&hand(&[Ace, King])));
+        assert!(dealer_blackjack(&hand(&[Ten, Ace])));
+        assert!(!dealer_blackjack(&hand(&[Ace])));
+        assert!(!dealer_blackjack(&hand(&[Ace, Five, Five])));
+        assert!(!dealer_blackjack(&hand(&[Six, Five, Ten])));
+    }
+
+    #[test]
+    fn peek() {
+        use Rank::*;
+
+        let mut rng = SmallRng::seed_from_u64(0_u64);
+        let only = |rank: fn(i32) -> bool| Shoe {
+            decks: 1,
+            remaining: CARDLIST
+                .iter()
+                .map(|c| rank(calc_score(&[*c])) as u32)
+                .collect(),
+            in_play: vec![],
+        };
+        let tens = only(|score| score == 10);
+        let aces = only(|score| score == 11);
+
+        assert_eq!(Some(true), peek_blackjack(&hand(&[Ace]), &tens, &mut rng));
+        assert_eq!(Some(true), peek_blackjack(&hand(&[Ten]), &aces, &mut rng));
+        assert_eq!(Some(false), peek_blackjack(&hand(&[Ace]), &aces, &mut rng));
+        assert_eq!(None, peek_blackjack(&hand(&[Nine]), &tens, &mut rng));
+
+        // the hole card drawn later matches the peek
+        let ace = BJCard::new(Ace, Suit::Spades);
+        let mut shoe = Shoe::new(1);
+        for _ in 0..10 {
+            assert!(is_blackjack(&[
+                ace,
+                draw_hole(ace, Some(true), &mut shoe, &mut rng)
+            ]));
+            assert!(!is_blackjack(&[
+                ace,
+                draw_hole(ace, Some(false), &mut shoe, &mut rng)
+            ]));
+        }
+        assert_eq!(32, shoe.len());
+    }
+
+    #[test]
+    fn perfect_pairs() {
+        use Rank::*;
+        use Suit::*;
+
+        let judge = |a: (Rank, Suit), b: (Rank, Suit)| {
+            judge_perfect_pairs(&[BJCard::new(a.0, a.1), BJCard::new(b.0, b.1)])
+        };
+        assert_eq!(PairResult::Perfect, judge((Eight, Hearts), (Eight, Hearts)));
+        assert_eq!(
+            PairResult::Colored,
+            judge((Eight, Hearts), (Eight, Diamonds))
+        );
+        assert_eq!(PairResult::Colored, judge((King, Spades), (King, Clubs)));
+        assert_eq!(PairResult::Mixed, judge((King, Spades), (King, Hearts)));
+        assert_eq!(PairResult::NoPair, judge((King, Spades), (Queen, Spades)));
+    }
+
+    #[test]
+    fn twenty_one_plus_three() {
+        use Rank::*;
+        use Suit::*;
+
+        let judge = |p1: (Rank, Suit), p2: (Rank, Suit), d: (Rank, Suit)| {
+            judge_twenty_one_plus_three(
+                &[BJCard::new(p1.0, p1.1), BJCard::new(p2.0, p2.1)],
+                &[BJCard::new(d.0, d.1)],
+            )
+        };
+        assert_eq!(
+            PokerJudge::SuitedTrips,
+            judge((Nine, Clubs), (Nine, Clubs), (Nine, Clubs))
+        );
+        assert_eq!(
+            PokerJudge::StraightFlush,
+            judge((Nine, Clubs), (Jack, Clubs), (Ten, Clubs))
+        );
+        assert_eq!(
+            PokerJudge::ThreeOfAKind,
+            judge((Nine, Clubs), (Nine, Hearts), (Nine, Clubs))
+        );
+        assert_eq!(
+            PokerJudge::Straight,
+            judge((Ace, Clubs), (Two, Hearts), (Three, Clubs))
+        );
+        assert_eq!(
+            PokerJudge::Straight,
+            judge((Queen, Clubs), (Ace, Hearts), (King, Clubs))
+        );
+        assert_eq!(
+            PokerJudge::Flush,
+            judge((Two, Spades), (Nine, Spades), (King, Spades))
+        );
+        assert_eq!(
+            PokerJudge::Nothing,
+            judge((King, Clubs), (Ace, Hearts), (Two, Clubs))
+        );
+        assert_eq!(
+            PokerJudge::Nothing,
+            judge((Nine, Clubs), (Nine, Hearts), (Two, Clubs))
+        );
+    }
+
     #[test]
     fn judge_draw() {
-        use BJCard::*;
+        use Rank::*;
 
-        let dealer = vec![Ten, Seven];
-        let player = vec![Ten, Seven];
+        let dealer = hand(&[Ten, Seven]);
+        let player = hand(&[Ten, Seven]);
         assert_eq!(Judge::Draw(17, 17), judge(&dealer, &player));
 
-        let dealer = vec![Ten, Ace];
-        let player = vec![Ten, Ace];
+        let dealer = hand(&[Ten, Ace]);
+        let player = hand(&[Ten, Ace]);
         assert_eq!(Judge::Draw(21, 21), judge(&dealer, &player));
     }
 }
@@ -0,0 +1,399 @@
+use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage};
+use cw_storage_plus::U64Key;
+
+use crate::cache::Cache;
+use crate::contract::{self, only_owner};
+use crate::error::ContractError;
+use crate::msg::{HouseChainResponse, HouseSeedResponse};
+use crate::random;
+use crate::state::{
+    HouseChain, Move, PendingMove, SeedRequest, HOUSE_CHAIN, HOUSE_SEEDS, SEED_REQUESTS,
+    SEED_REQUEST_COUNT,
+};
+use crate::{table, tournament};
+
+/// Moves that can wait on one house seed, all drawn when it is revealed.
+pub const MAX_WAITING: usize = 20;
+
+/// Owner only. `anchor` is the last hash of a chain of secret seeds, each the sha256
+/// of the next. Moves after it wait on the seeds hashing down to it, one per index.
+/// A new anchor can be committed before the chain runs out, or to give up on one.
+pub fn try_commit_house_chain(
+    deps: DepsMut,
+    info: MessageInfo,
+    anchor: Binary,
+) -> Result<Response, ContractError> {
+    only_owner(deps.storage, &info)?;
+
+    if anchor.len() != random::COMMITMENT_LENGTH {
+        return Err(ContractError::InvalidCommitment {});
+    }
+
+    let index = HOUSE_CHAIN
+        .may_load(deps.storage)?
+        .map(|chain| chain.next)
+        .unwrap_or_default();
+    HOUSE_SEEDS.save(deps.storage, U64Key::new(index), &anchor)?;
+    let chain = HouseChain {
+        anchor: index,
+        next: index + 1,
+    };
+    HOUSE_CHAIN.save(deps.storage, &chain)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "commit_house_chain")
+        .add_attribute("index", index.to_string()))
+}
+
+/// A new move waits on the next house seed revealed, along with every move made before
+/// it is. Which seed that is doesn't depend on the moves made before, and the block time
+/// of the move, mixed in with the seeds, is unknown to the house until the move is in.
+pub(crate) fn request_seed(
+    storage: &mut dyn Storage,
+    env: &Env,
+    made_for: Move,
+    request: &SeedRequest,
+) -> Result<PendingMove, ContractError> {
+    let chain = HOUSE_CHAIN
+        .may_load(storage)?
+        .ok_or(ContractError::NoHouseChain {})?;
+    let waiting = SEED_REQUESTS
+        .prefix(U64Key::new(chain.next))
+        .keys(storage, None, None, Order::Ascending)
+        .take(MAX_WAITING)
+        .count();
+    if waiting >= MAX_WAITING {
+        return Err(ContractError::HouseBusy {});
+    }
+
+    let key = SEED_REQUEST_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    SEED_REQUEST_COUNT.save(storage, &key)?;
+    SEED_REQUESTS.save(
+        storage,
+        (U64Key::new(chain.next), U64Key::new(key)),
+        request,
+    )?;
+    Ok(PendingMove {
+        made_for,
+        index: chain.next,
+        request: key,
+        made_at: env.block.time,
+    })
+}
+
+/// A move settled without its house seed no longer waits on it.
+pub(crate) fn cancel_request(storage: &mut dyn Storage, pending: &PendingMove) {
+    SEED_REQUESTS.remove(
+        storage,
+        (U64Key::new(pending.index), U64Key::new(pending.request)),
+    );
+}
+
+/// The seed at `index` must hash to the one before it, so seeds are revealed in order.
+/// The moves waiting on it are drawn right away. A draw that fails leaves its move as
+/// it was, to settle on the house timeout, and the chain goes on. Anyone can call it.
+pub fn try_reveal_house_seed(
+    deps: DepsMut,
+    env: Env,
+    index: u64,
+    seed: Binary,
+) -> Result<Response, ContractError> {
+    let mut chain = HOUSE_CHAIN
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoHouseChain {})?;
+    if index < chain.next {
+        return Err(ContractError::SeedNotWaitedOn { index });
+    }
+    if index > chain.next {
+        return Err(ContractError::SeedOutOfOrder { index });
+    }
+    let previous = HOUSE_SEEDS.load(deps.storage, U64Key::new(index - 1))?;
+    if !random::verify_seed(&seed, &previous) {
+        return Err(ContractError::HouseSeedMismatch {});
+    }
+    HOUSE_SEEDS.save(deps.storage, U64Key::new(index), &seed)?;
+    chain.next += 1;
+    HOUSE_CHAIN.save(deps.storage, &chain)?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "reveal_house_seed")
+        .add_attribute("index", index.to_string());
+
+    let requests = SEED_REQUESTS
+        .prefix(U64Key::new(index))
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (key, request) in requests {
+        SEED_REQUESTS.remove(deps.storage, (U64Key::new(index), U64Key::from(key)));
+        let mut cache = Cache::new(deps.storage);
+        match draw(&mut cache, &env, request, &seed) {
+            Ok(drawn) => {
+                cache.commit();
+                res = res
+                    .add_attributes(drawn.attributes)
+                    .add_submessages(drawn.messages);
+            }
+            Err(err) => res = res.add_attribute("draw_failed", err.to_string()),
+        }
+    }
+
+    Ok(res)
+}
+
+fn draw(
+    storage: &mut dyn Storage,
+    env: &Env,
+    request: SeedRequest,
+    seed: &Binary,
+) -> Result<Response, ContractError> {
+    match request {
+        SeedRequest::Game { player, token } => {
+            contract::draw_game(storage, env, &player, &token, seed)
+        }
+        SeedRequest::Table { table_id } => table::draw_table(storage, env, table_id, seed),
+        SeedRequest::Tournament {
+            tournament_id,
+            player,
+        } => tournament::draw_tournament(storage, env, tournament_id, &player, seed),
+    }
+}
+
+pub fn query_house_chain(deps: Deps) -> StdResult<HouseChainResponse> {
+    let chain = HOUSE_CHAIN.may_load(deps.storage)?;
+
+    Ok(HouseChainResponse { chain })
+}
+
+pub fn query_house_seed(deps: Deps, index: u64) -> StdResult<HouseSeedResponse> {
+    let seed = HOUSE_SEEDS.may_load(deps.storage, U64Key::new(index))?;
+
+    Ok(HouseSeedResponse { index, seed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::{execute, query};
+    use crate::msg::{
+        ActionCommand, ConfigResponse, DepositResponse, ExecuteMsg, GameStateResponce, QueryMsg,
+    };
+    use crate::state::{GAME_ENTROPY, SHOE};
+    use crate::testing::{self, init_with_balance, seed, seed_commitment, TestDeps};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, Addr, Uint128};
+
+    fn bet(deps: &mut TestDeps, player: &str) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment(player),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg)
+    }
+
+    fn reveal(deps: &mut TestDeps, index: u64, seed: Binary) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::RevealHouseSeed { index, seed };
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg)
+    }
+
+    fn game_state(deps: &TestDeps, player: &str) -> GameStateResponce {
+        let msg = QueryMsg::GetGameState {
+            address: player.to_string(),
+            token: testing::token("token0000"),
+        };
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn commit_and_reveal() {
+        // no move before the house committed
+        let mut deps = mock_dependencies(&[]);
+        let msg = testing::instantiate_msg();
+        crate::contract::instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .unwrap();
+        testing::deposit(&mut deps, "user0000", 1000);
+        testing::provide_liquidity(&mut deps, 1_000_000);
+        assert_eq!(
+            ContractError::NoHouseChain {},
+            bet(&mut deps, "user0000").unwrap_err()
+        );
+
+        // only the owner commits, to a sha256 digest
+        let msg = ExecuteMsg::CommitHouseChain {
+            anchor: testing::house_seed(0),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err);
+        let msg = ExecuteMsg::CommitHouseChain {
+            anchor: Binary::from(b"short"),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::InvalidCommitment {}, err);
+        let msg = ExecuteMsg::CommitHouseChain {
+            anchor: testing::house_seed(0),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // the bet waits on the next seed, no card is dealt yet
+        let res = bet(&mut deps, "user0000").unwrap();
+        assert_eq!(3, res.attributes.len());
+        let state = game_state(&deps, "user0000");
+        assert!(state.state.dealer_hand.is_empty());
+        assert_eq!(1, state.state.pending.unwrap().index);
+
+        let msg = ExecuteMsg::Action {
+            token: testing::token("token0000"),
+            action: ActionCommand::Stand,
+            seed: Some(seed("user0000")),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap_err();
+        assert_eq!(ContractError::SeedPending {}, err);
+
+        // seeds are revealed in order
+        let err = reveal(&mut deps, 2, testing::house_seed(2)).unwrap_err();
+        assert_eq!(ContractError::SeedOutOfOrder { index: 2 }, err);
+        let err = reveal(&mut deps, 1, testing::house_seed(2)).unwrap_err();
+        assert_eq!(ContractError::HouseSeedMismatch {}, err);
+
+        // a bet before the reveal waits on the same seed
+        testing::deposit(&mut deps, "user0001", 1000);
+        bet(&mut deps, "user0001").unwrap();
+        assert_eq!(
+            1,
+            game_state(&deps, "user0001").state.pending.unwrap().index
+        );
+
+        // anyone can reveal it, the cards of both are dealt with it
+        let res = reveal(&mut deps, 1, testing::house_seed(1)).unwrap();
+        assert_eq!("reveal_house_seed", res.attributes[0].value);
+        assert_eq!("dealer_cards", res.attributes[2].key);
+        for player in ["user0000", "user0001"] {
+            let state = game_state(&deps, player);
+            assert_eq!(1, state.state.dealer_hand.len());
+            assert_eq!(None, state.state.pending);
+        }
+        let err = reveal(&mut deps, 1, testing::house_seed(1)).unwrap_err();
+        assert_eq!(ContractError::SeedNotWaitedOn { index: 1 }, err);
+        reveal(&mut deps, 2, testing::house_seed(2)).unwrap();
+
+        let res: HouseSeedResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetHouseSeed { index: 2 },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Some(testing::house_seed(2)), res.seed);
+
+        // a new anchor takes the next index
+        let msg = ExecuteMsg::CommitHouseChain {
+            anchor: testing::house_seed(0),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!("3", res.attributes[1].value);
+        let res: HouseChainResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetHouseChain {}).unwrap())
+                .unwrap();
+        assert_eq!(Some(HouseChain { anchor: 3, next: 4 }), res.chain);
+        let err = reveal(&mut deps, 3, testing::house_seed(1)).unwrap_err();
+        assert_eq!(ContractError::SeedNotWaitedOn { index: 3 }, err);
+    }
+
+    #[test]
+    fn other_moves_change_nothing() {
+        // the cards of user0000, with and without a bet of user0001 first
+        let dealt = |other: bool| {
+            let mut deps = init_with_balance();
+            if other {
+                testing::deposit(&mut deps, "user0001", 1000);
+                bet(&mut deps, "user0001").unwrap();
+            }
+            bet(&mut deps, "user0000").unwrap();
+            reveal(&mut deps, 1, testing::house_seed(1)).unwrap();
+            let state = game_state(&deps, "user0000").state;
+            (state.dealer_hand, state.player_hands)
+        };
+        assert_eq!(dealt(false), dealt(true));
+    }
+
+    #[test]
+    fn failed_draw() {
+        let mut deps = init_with_balance();
+        testing::deposit(&mut deps, "user0001", 1000);
+        bet(&mut deps, "user0000").unwrap();
+        bet(&mut deps, "user0001").unwrap();
+
+        // the shoe of user0000 can't be read, after its house seed was kept
+        let player = Addr::unchecked("user0000");
+        let token = testing::token("token0000");
+        deps.storage.set(&SHOE.key((&player, &token)), b"broken");
+        let res = reveal(&mut deps, 1, testing::house_seed(1)).unwrap();
+        assert_eq!("draw_failed", res.attributes[2].key);
+        assert_eq!("dealer_cards", res.attributes[3].key);
+
+        // nothing of it is kept, the game settles on the house timeout
+        let entropy = GAME_ENTROPY.load(&deps.storage, (&player, &token)).unwrap();
+        assert!(entropy.house_seeds.is_empty());
+        assert!(game_state(&deps, "user0000").state.pending.is_some());
+        assert_eq!(1, game_state(&deps, "user0001").state.dealer_hand.len());
+
+        // and the chain goes on
+        bet(&mut deps, "user0000").unwrap_err();
+        let msg = ExecuteMsg::Action {
+            token: token.clone(),
+            action: ActionCommand::Stand,
+            seed: Some(seed("user0001")),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("user0001", &[]), msg).unwrap();
+        reveal(&mut deps, 2, testing::house_seed(2)).unwrap();
+        assert!(!game_state(&deps, "user0001").state.ingame);
+    }
+
+    #[test]
+    fn house_timeout() {
+        let mut deps = init_with_balance();
+        bet(&mut deps, "user0000").unwrap();
+
+        // the house has the same timeout as the player to reveal
+        let mut env = mock_env();
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(crate::contract::DEFAULT_TIMEOUT);
+        let msg = ExecuteMsg::ForceSettle {
+            address: "user0000".to_string(),
+            token: testing::token("token0000"),
+        };
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!("end", res.attributes[2].value);
+        assert_eq!("Win", res.attributes[3].value);
+        assert_eq!("HouseTimeout 0", res.attributes[5].value);
+
+        // all it reserved goes to the player
+        let config: ConfigResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap())
+                .unwrap();
+        let reserved = config.config.payout_table.max_payout(Uint128::new(100));
+        let msg = QueryMsg::GetDeposit {
+            address: "user0000".to_string(),
+            asset: testing::token("token0000"),
+        };
+        let res: DepositResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(Uint128::new(900) + reserved, res.deposit);
+        assert!(!game_state(&deps, "user0000").state.ingame);
+
+        // a late seed draws nothing, the chain goes on
+        let res = reveal(&mut deps, 1, testing::house_seed(1)).unwrap();
+        assert_eq!(2, res.attributes.len());
+        bet(&mut deps, "user0000").unwrap();
+        assert_eq!(
+            2,
+            game_state(&deps, "user0000").state.pending.unwrap().index
+        );
+    }
+}
@@ -2,6 +2,7 @@
 mod tests {
     use crate::helpers::CwTemplateContract;
     use crate::msg::InstantiateMsg;
+    use crate::testing::instantiate_msg;
     use cosmwasm_std::{Addr, Empty};
     use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
 
@@ -10,7 +11,8 @@ mod tests {
             crate::contract::execute,
             crate::contract::instantiate,
             crate::contract::query,
-        );
+        )
+        .with_migrate(crate::contract::migrate);
         Box::new(contract)
     }
 
@@ -42,9 +44,7 @@ mod tests {
         let mut app = mock_app();
         let cw_template_id = app.store_code(contract_template());
 
-        let msg = InstantiateMsg {
-            cw20_address: "token0000".to_string(),
-        };
+        let msg = instantiate_msg();
         let cw_template_contract_addr = app
             .instantiate_contract(
                 cw_template_id,
@@ -52,7 +52,7 @@ mod tests {
                 &msg,
                 &[],
                 "test",
-                None,
+                Some(ADMIN.to_string()),
             )
             .unwrap();
 
@@ -74,4 +74,157 @@ mod tests {
             // app.execute(Addr::unchecked(USER), cosmos_msg).unwrap();
         }
     }
+
+    mod migrate {
+        use super::*;
+        use crate::migration::v0_1;
+        use crate::msg::{
+            DepositResponse, GameStateResponce, MigrateMsg, QueryMsg, TokensResponse,
+        };
+        use crate::state::AssetInfo;
+        use crate::ContractError;
+        use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Uint128};
+        use cw2::{query_contract_info, set_contract_version};
+
+        const USER: &str = "user0000";
+
+        /// Stands in for a 0.1 instance with a game in progress.
+        fn v0_1_instantiate(
+            deps: DepsMut,
+            _env: Env,
+            info: MessageInfo,
+            msg: InstantiateMsg,
+        ) -> Result<Response, ContractError> {
+            set_contract_version(deps.storage, v0_1::CONTRACT_NAME, "0.1.0")?;
+            v0_1::STATE.save(deps.storage, &v0_1::State { owner: info.sender })?;
+            v0_1::CONFIG.save(
+                deps.storage,
+                &v0_1::Config {
+                    token_address: Addr::unchecked(msg.cw20_address),
+                },
+            )?;
+
+            let player = Addr::unchecked(USER);
+            v0_1::VAULT.save(
+                deps.storage,
+                &player,
+                &v0_1::Vault {
+                    balance: Uint128::new(900),
+                },
+            )?;
+            v0_1::GAMESTATE.save(
+                deps.storage,
+                &player,
+                &v0_1::GameState {
+                    ingame: true,
+                    total_bet_amount: Uint128::new(100),
+                    dealer_hand: vec![v0_1::BJCard::Queeen],
+                    player_hand: vec![v0_1::BJCard::Ace, v0_1::BJCard::Five],
+                },
+            )?;
+            Ok(Response::new())
+        }
+
+        fn v0_1_contract() -> Box<dyn Contract<Empty>> {
+            let contract = ContractWrapper::new(
+                crate::contract::execute,
+                v0_1_instantiate,
+                crate::contract::query,
+            );
+            Box::new(contract)
+        }
+
+        #[test]
+        fn from_v0_1() {
+            let mut app = mock_app();
+            let old_id = app.store_code(v0_1_contract());
+            let new_id = app.store_code(contract_template());
+
+            let msg = instantiate_msg();
+            let addr = app
+                .instantiate_contract(
+                    old_id,
+                    Addr::unchecked(ADMIN),
+                    &msg,
+                    &[],
+                    "test",
+                    Some(ADMIN.to_string()),
+                )
+                .unwrap();
+
+            // only the admin can migrate
+            app.migrate_contract(Addr::unchecked(USER), addr.clone(), &MigrateMsg {}, new_id)
+                .unwrap_err();
+            app.migrate_contract(Addr::unchecked(ADMIN), addr.clone(), &MigrateMsg {}, new_id)
+                .unwrap();
+
+            let info = query_contract_info(&app, addr.to_string()).unwrap();
+            assert_eq!("crates.io:bj-game", info.contract);
+            assert_eq!(env!("CARGO_PKG_VERSION"), info.version);
+
+            let res: GameStateResponce = app
+                .wrap()
+                .query_wasm_smart(
+                    addr.clone(),
+                    &QueryMsg::GetGameState {
+                        address: USER.to_string(),
+                        token: AssetInfo::cw20(&Addr::unchecked("token0000")),
+                    },
+                )
+                .unwrap();
+            assert!(!res.state.ingame);
+            assert_eq!(1, res.state.player_hands.len());
+            assert_eq!(2, res.state.player_hands[0].cards.len());
+            assert_eq!(Uint128::new(100), res.state.player_hands[0].bet_amount);
+
+            // the unfinished bet is back in the vault
+            let res: DepositResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    addr.clone(),
+                    &QueryMsg::GetDeposit {
+                        address: USER.to_string(),
+                        asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+                    },
+                )
+                .unwrap();
+            assert_eq!(Uint128::new(1000), res.deposit);
+
+            let res: TokensResponse = app
+                .wrap()
+                .query_wasm_smart(addr.clone(), &QueryMsg::GetTokens {})
+                .unwrap();
+            assert_eq!(
+                AssetInfo::cw20(&Addr::unchecked("token0000")),
+                res.tokens[0].token
+            );
+
+            // migrating again to the same version is a no-op
+            app.migrate_contract(Addr::unchecked(ADMIN), addr, &MigrateMsg {}, new_id)
+                .unwrap();
+        }
+
+        #[test]
+        fn live_instance() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+            let new_id = app.store_code(contract_template());
+
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &MigrateMsg {},
+                new_id,
+            )
+            .unwrap();
+
+            let res: TokensResponse = app
+                .wrap()
+                .query_wasm_smart(cw_template_contract.addr(), &QueryMsg::GetTokens {})
+                .unwrap();
+            assert_eq!(
+                AssetInfo::cw20(&Addr::unchecked("token0000")),
+                res.tokens[0].token
+            );
+        }
+    }
 }
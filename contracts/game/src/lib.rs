@@ -1,11 +1,21 @@
+mod cache;
 mod card;
 pub mod contract;
 mod error;
 mod game;
 pub mod helpers;
+mod house;
 pub mod integration_tests;
+mod migration;
 pub mod msg;
+mod play;
 mod random;
+mod shoe;
 pub mod state;
+mod table;
+#[cfg(test)]
+mod testing;
+mod tournament;
+mod verify;
 
 pub use crate::error::ContractError;
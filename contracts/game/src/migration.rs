@@ -0,0 +1,310 @@
+use cosmwasm_std::{
+    Addr, Binary, Decimal, Order, StdError, StdResult, Storage, Timestamp, Uint128,
+};
+
+use crate::card::{BJCard, Hand, Rank, Suit};
+use crate::contract::{DEFAULT_DECKS, DEFAULT_PENETRATION, DEFAULT_TIMEOUT};
+use crate::error::ContractError;
+use crate::state::{
+    AssetInfo, Bankroll, Config, GameState, PlayerHand, State, TokenConfig, Vault, BANKROLL,
+    CONFIG, GAMESTATE, STATE, TOKENS, VAULT,
+};
+
+/// Layout of 0.1, released as "crates.io:project-name".
+/// One hand per game and cards without suits.
+pub(crate) mod v0_1 {
+    use cosmwasm_std::{Addr, Uint128};
+    use cw_storage_plus::{Item, Map};
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    pub const CONTRACT_NAME: &str = "crates.io:project-name";
+
+    #[derive(
+        Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, JsonSchema,
+    )]
+    pub enum BJCard {
+        Two,
+        Three,
+        Four,
+        Five,
+        Six,
+        Seven,
+        Eight,
+        Nine,
+        Ten,
+        Jack,
+        Queeen,
+        King,
+        Ace,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct State {
+        pub owner: Addr,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct Config {
+        pub token_address: Addr,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct Vault {
+        pub balance: Uint128,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct GameState {
+        pub ingame: bool,
+        pub total_bet_amount: Uint128,
+        pub dealer_hand: Vec<BJCard>,
+        pub player_hand: Vec<BJCard>,
+    }
+
+    pub const STATE: Item<State> = Item::new("state");
+    pub const CONFIG: Item<Config> = Item::new("config");
+    pub const VAULT: Map<&Addr, Vault> = Map::new("vault");
+    pub const GAMESTATE: Map<&Addr, GameState> = Map::new("gamestate");
+}
+
+/// Reads the keys of a 0.1 map, which are all addresses.
+fn v0_1_addr(key: Vec<u8>) -> StdResult<Addr> {
+    let addr = String::from_utf8(key).map_err(|_| StdError::invalid_utf8("v0.1 address key"))?;
+    Ok(Addr::unchecked(addr))
+}
+
+/// The suits were never stored, so every old card becomes a spade.
+fn card_from_v0_1(card: v0_1::BJCard) -> BJCard {
+    use v0_1::BJCard as Old;
+
+    let rank = match card {
+        Old::Two => Rank::Two,
+        Old::Three => Rank::Three,
+        Old::Four => Rank::Four,
+        Old::Five => Rank::Five,
+        Old::Six => Rank::Six,
+        Old::Seven => Rank::Seven,
+        Old::Eight => Rank::Eight,
+        Old::Nine => Rank::Nine,
+        Old::Ten => Rank::Ten,
+        Old::Jack => Rank::Jack,
+        Old::Queeen => Rank::Queen,
+        Old::King => Rank::King,
+        Old::Ace => Rank::Ace,
+    };
+    BJCard::new(rank, Suit::Spades)
+}
+
+fn hand_from_v0_1(hand: Vec<v0_1::BJCard>) -> Hand {
+    hand.into_iter().map(card_from_v0_1).collect()
+}
+
+/// Moves 0.1 data to the current layout and returns the number of refunded games.
+///
+/// The game token is whitelisted with the instantiate defaults and its bankroll starts
+/// empty, so new bets wait for liquidity. Vaults and games move under the token.
+/// Old games have no seed commitment to finish with, so the ones in progress are
+/// closed and their bets go back to the players' vaults.
+pub fn migrate_from_v0_1(storage: &mut dyn Storage) -> Result<u32, ContractError> {
+    let state = v0_1::STATE.load(storage)?;
+    STATE.save(
+        storage,
+        &State {
+            owner: state.owner,
+            pending_owner: None,
+            paused: false,
+        },
+    )?;
+
+    let config = v0_1::CONFIG.load(storage)?;
+    CONFIG.save(
+        storage,
+        &Config {
+            insurance: false,
+            payout_table: Default::default(),
+            decks: DEFAULT_DECKS,
+            penetration: Decimal::percent(DEFAULT_PENETRATION),
+            dealer_hits_soft_17: false,
+            perfect_pairs: Default::default(),
+            twenty_one_plus_three: Default::default(),
+            timeout: DEFAULT_TIMEOUT,
+            timeout_action: Default::default(),
+        },
+    )?;
+    let token = AssetInfo::cw20(&config.token_address);
+    TOKENS.save(storage, &token, &TokenConfig::default())?;
+    BANKROLL.save(storage, &token, &Bankroll::default())?;
+
+    let vaults = v0_1::VAULT
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, vault) = item?;
+            Ok((v0_1_addr(key)?, vault))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for (player, vault) in vaults {
+        v0_1::VAULT.remove(storage, &player);
+        VAULT.save(
+            storage,
+            (&player, &token),
+            &Vault {
+                balance: vault.balance,
+            },
+        )?;
+    }
+
+    let games = v0_1::GAMESTATE
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, game) = item?;
+            Ok((v0_1_addr(key)?, game))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut refunded = 0;
+    for (player, old) in games {
+        if old.ingame {
+            VAULT.update(storage, (&player, &token), |vault| -> StdResult<_> {
+                let balance = vault.map(|v| v.balance).unwrap_or_default();
+                Ok(Vault {
+                    balance: balance.checked_add(old.total_bet_amount)?,
+                })
+            })?;
+            refunded += 1;
+        }
+
+        let game = GameState {
+            ingame: false,
+            total_bet_amount: old.total_bet_amount,
+            dealer_hand: hand_from_v0_1(old.dealer_hand),
+            dealer_blackjack: None,
+            player_hands: vec![PlayerHand {
+                cards: hand_from_v0_1(old.player_hand),
+                bet_amount: old.total_bet_amount,
+            }],
+            active_hand: 0,
+            insurance_amount: Uint128::zero(),
+            perfect_pairs_amount: Uint128::zero(),
+            twenty_one_plus_three_amount: Uint128::zero(),
+            side_payout: Uint128::zero(),
+            liability: Uint128::zero(),
+            seed_commitment: Binary::default(),
+            player_seed: None,
+            pending: None,
+            started_height: 0,
+            started_at: Timestamp::from_nanos(0),
+            turn_started: Timestamp::from_nanos(0),
+            auto_withdraw: false,
+        };
+        v0_1::GAMESTATE.remove(storage, &player);
+        GAMESTATE.save(storage, (&player, &token), &game)?;
+    }
+
+    Ok(refunded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    #[test]
+    fn from_v0_1() {
+        let mut storage = MockStorage::new();
+        let player = Addr::unchecked("user0000");
+        let idle = Addr::unchecked("user0001");
+        v0_1::STATE
+            .save(
+                &mut storage,
+                &v0_1::State {
+                    owner: Addr::unchecked("creator"),
+                },
+            )
+            .unwrap();
+        v0_1::CONFIG
+            .save(
+                &mut storage,
+                &v0_1::Config {
+                    token_address: Addr::unchecked("token0000"),
+                },
+            )
+            .unwrap();
+        v0_1::VAULT
+            .save(
+                &mut storage,
+                &player,
+                &v0_1::Vault {
+                    balance: Uint128::new(900),
+                },
+            )
+            .unwrap();
+        v0_1::GAMESTATE
+            .save(
+                &mut storage,
+                &player,
+                &v0_1::GameState {
+                    ingame: true,
+                    total_bet_amount: Uint128::new(100),
+                    dealer_hand: vec![v0_1::BJCard::Queeen],
+                    player_hand: vec![v0_1::BJCard::Ace, v0_1::BJCard::Five],
+                },
+            )
+            .unwrap();
+        v0_1::GAMESTATE
+            .save(
+                &mut storage,
+                &idle,
+                &v0_1::GameState {
+                    ingame: false,
+                    total_bet_amount: Uint128::new(10),
+                    dealer_hand: vec![v0_1::BJCard::Ten, v0_1::BJCard::Nine],
+                    player_hand: vec![v0_1::BJCard::Two, v0_1::BJCard::Three],
+                },
+            )
+            .unwrap();
+
+        assert_eq!(1, migrate_from_v0_1(&mut storage).unwrap());
+
+        let state = STATE.load(&storage).unwrap();
+        assert_eq!(Addr::unchecked("creator"), state.owner);
+        assert!(!state.paused);
+        let token = AssetInfo::cw20(&Addr::unchecked("token0000"));
+        let config = CONFIG.load(&storage).unwrap();
+        assert_eq!(DEFAULT_DECKS, config.decks);
+        assert!(TOKENS.has(&storage, &token));
+        assert_eq!(
+            Bankroll::default(),
+            BANKROLL.load(&storage, &token).unwrap()
+        );
+
+        // the game in progress is refunded
+        let game = GAMESTATE.load(&storage, (&player, &token)).unwrap();
+        assert!(!game.ingame);
+        assert_eq!(
+            vec![BJCard::new(Rank::Queen, Suit::Spades)],
+            game.dealer_hand
+        );
+        assert_eq!(
+            vec![PlayerHand {
+                cards: vec![
+                    BJCard::new(Rank::Ace, Suit::Spades),
+                    BJCard::new(Rank::Five, Suit::Spades)
+                ],
+                bet_amount: Uint128::new(100),
+            }],
+            game.player_hands
+        );
+        assert_eq!(
+            Uint128::new(1000),
+            VAULT.load(&storage, (&player, &token)).unwrap().balance
+        );
+
+        let game = GAMESTATE.load(&storage, (&idle, &token)).unwrap();
+        assert!(!game.ingame);
+        assert_eq!(1, game.player_hands.len());
+        assert!(VAULT.may_load(&storage, (&idle, &token)).unwrap().is_none());
+        assert!(v0_1::VAULT.may_load(&storage, &player).unwrap().is_none());
+        assert!(v0_1::GAMESTATE.may_load(&storage, &idle).unwrap().is_none());
+    }
+}
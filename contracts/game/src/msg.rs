@@ -1,13 +1,42 @@
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Binary, Decimal, Timestamp, Uint128};
 use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::GameState;
+use crate::card::Hand;
+use crate::game::Score;
+use crate::shoe::Shoe;
+use crate::state::{
+    AssetInfo, BetLimits, Config, Entry, GameState, HouseChain, PayoutTable, PerfectPairsTable,
+    Round, RoundEntropy, Stats, Table, TimeoutAction, Tournament, TwentyOnePlusThreeTable,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
+    /// first whitelisted token, limited by `bet_limits`
     pub cw20_address: String,
+    /// offer insurance when the dealer shows an ace. default false
+    pub insurance: Option<bool>,
+    /// default 3:2 blackjack
+    pub payout_table: Option<PayoutTable>,
+    /// number of decks in the shoe, 1 to 8. default 6
+    pub decks: Option<u8>,
+    /// share of the shoe dealt before reshuffling. default 0.75
+    pub penetration: Option<Decimal>,
+    /// dealer hits soft 17 (H17). default false (S17)
+    pub dealer_hits_soft_17: Option<bool>,
+    /// default 6:1 mixed, 12:1 colored and 25:1 perfect pairs
+    pub perfect_pairs: Option<PerfectPairsTable>,
+    /// default 5:1 flush, 10:1 straight, 30:1 three of a kind,
+    /// 40:1 straight flush and 100:1 suited trips
+    pub twenty_one_plus_three: Option<TwentyOnePlusThreeTable>,
+    /// default minimum bet of 1 without a maximum
+    pub bet_limits: Option<BetLimits>,
+    /// seconds a game may wait on the player or the house before anyone can force settle it.
+    /// default one day
+    pub timeout: Option<u64>,
+    /// default stand
+    pub timeout_action: Option<TimeoutAction>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -15,45 +44,453 @@ pub struct InstantiateMsg {
 pub enum ActionCommand {
     Stand,
     Hit,
-    DoubleDown { amount: Uint128 },
+    DoubleDown {
+        amount: Uint128,
+    },
+    /// Split a pair into two hands. `amount` must match the bet of the hand.
+    Split {
+        amount: Uint128,
+    },
+    /// Side bet of up to half the bet that the dealer has blackjack.
+    /// Only offered when the dealer shows an ace, before any other action.
+    Insurance {
+        amount: Uint128,
+    },
+    /// Give up the hand on the first two cards and get half of the bet back.
+    Surrender,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     /// Receives a message of type [`Cw20ReceiveMsg`]
     Receive(Cw20ReceiveMsg),
+    /// Start a game in a whitelisted `token`, paid from its vault balance.
+    /// `seed_commitment` is the sha256 hash of a secret seed
+    /// which must be revealed on the first action.
     Bet {
+        token: AssetInfo,
         amount: Uint128,
+        seed_commitment: Binary,
+        /// Perfect Pairs side bet on the player's first two cards
+        perfect_pairs: Option<Uint128>,
+        /// 21+3 side bet on the player's first two cards and the dealer's up card
+        twenty_one_plus_three: Option<Uint128>,
     },
+    /// Play the game in `token`
     Action {
+        token: AssetInfo,
         action: ActionCommand,
+        seed: Option<Binary>,
     },
+    /// Resolve a game left open past the timeout. Anyone can call it.
+    ForceSettle {
+        address: String,
+        token: AssetInfo,
+    },
+    /// Deposit the bank coins sent, each in a whitelisted denom
+    Deposit {},
     Withdraw {
         amount: Uint128,
+        asset: AssetInfo,
+    },
+    /// Add the bank coins sent to the bankroll of their whitelisted denom for LP shares.
+    /// cw20s are provided with [`Cw20HookMsg::ProvideLiquidity`]
+    ProvideLiquidity {},
+    /// Burn LP shares for their part of the bankroll of `token`
+    WithdrawLiquidity {
+        token: AssetInfo,
+        shares: Uint128,
+    },
+    /// Owner only
+    UpdateConfig(UpdateConfigMsg),
+    /// Owner only. Whitelist a cw20 or a bank denom, or update its limits
+    AddToken {
+        token: AssetInfo,
+        /// default minimum bet of 1 without a maximum
+        bet_limits: Option<BetLimits>,
+    },
+    /// Owner only. No new bets or liquidity in the token.
+    /// Games in progress, vaults and liquidity can still be closed
+    RemoveToken {
+        token: AssetInfo,
+    },
+    /// Owner only. Open a table in a whitelisted `token`
+    OpenTable {
+        token: AssetInfo,
+        /// seconds the betting stays open after the first bet. default 60
+        betting_window: Option<u64>,
+        /// seconds a seat has to act. default 300
+        turn_timeout: Option<u64>,
+    },
+    /// Take a free seat. Seats taken during a round play from the next one
+    JoinTable {
+        table_id: u64,
+    },
+    /// Leave a seat without a bet, between rounds
+    LeaveTable {
+        table_id: u64,
+    },
+    /// Bet on the seat's next hand, paid from the vault balance of the table token.
+    /// The first bet of a round starts the betting window
+    TableBet {
+        table_id: u64,
+        amount: Uint128,
+        seed_commitment: Binary,
+    },
+    /// Deal once the betting window closed or every seat has bet. Anyone can call it
+    DealTable {
+        table_id: u64,
+    },
+    /// Play the seat's hand when it is its turn.
+    /// Only stand, hit and double down are offered
+    TableAction {
+        table_id: u64,
+        action: ActionCommand,
+        seed: Option<Binary>,
+    },
+    /// Stand the seat to act once its turn timed out. Anyone can call it
+    ForceStand {
+        table_id: u64,
+    },
+    /// Owner only. Create a tournament in a whitelisted `token`,
+    /// played for a number of rounds, until an end time or both
+    CreateTournament {
+        token: AssetInfo,
+        buy_in: Uint128,
+        starting_chips: Uint128,
+        starts_at: Timestamp,
+        ends_at: Option<Timestamp>,
+        rounds: Option<u32>,
+        /// share of the prize pool for each place, from the first. must add up to one
+        payouts: Vec<Decimal>,
+    },
+    /// Pay the buy-in from the vault balance of the tournament token, before the start
+    RegisterTournament {
+        tournament_id: u64,
+    },
+    /// Start a round with tournament chips, as in [`ExecuteMsg::Bet`]
+    TournamentBet {
+        tournament_id: u64,
+        amount: Uint128,
+        seed_commitment: Binary,
+    },
+    /// Play the tournament round. Splits and insurance are not offered
+    TournamentAction {
+        tournament_id: u64,
+        action: ActionCommand,
+        seed: Option<Binary>,
+    },
+    /// Pay out the prize pool once the tournament is over. Anyone can call it
+    SettleTournament {
+        tournament_id: u64,
+    },
+    /// Owner only. The new owner takes over once accepted
+    ProposeNewOwner {
+        owner: String,
+    },
+    AcceptOwnership {},
+    /// Owner only. Commit to a new chain of house seeds by its last hash
+    CommitHouseChain {
+        anchor: Binary,
+    },
+    /// Reveal the next house seed, which hashes to the one before it, and draw the moves
+    /// waiting on it. Anyone can call it
+    RevealHouseSeed {
+        index: u64,
+        seed: Binary,
     },
+    /// Owner only. Stop new bets
+    Pause {},
+    /// Owner only
+    Unpause {},
+}
+
+/// Fields left out keep their current value.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct UpdateConfigMsg {
+    pub insurance: Option<bool>,
+    pub payout_table: Option<PayoutTable>,
+    pub decks: Option<u8>,
+    pub penetration: Option<Decimal>,
+    pub dealer_hits_soft_17: Option<bool>,
+    pub perfect_pairs: Option<PerfectPairsTable>,
+    pub twenty_one_plus_three: Option<TwentyOnePlusThreeTable>,
+    pub timeout: Option<u64>,
+    pub timeout_action: Option<TimeoutAction>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
     Deposit {},
+    /// Deposit the tokens sent and start a game with them, as in [`ExecuteMsg::Bet`]
+    Bet {
+        /// defaults to the amount sent
+        amount: Option<Uint128>,
+        seed_commitment: Binary,
+        perfect_pairs: Option<Uint128>,
+        twenty_one_plus_three: Option<Uint128>,
+        /// send the payout back at settlement instead of crediting the vault
+        auto_withdraw: Option<bool>,
+    },
+    /// Add funds to the house bankroll for LP shares
+    ProvideLiquidity {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    GetDeposit { address: String },
-    GetGameState { address: String },
+    GetDeposit {
+        address: String,
+        asset: AssetInfo,
+    },
+    GetGameState {
+        address: String,
+        token: AssetInfo,
+    },
+    GetConfig {},
+    /// Whitelisted tokens
+    GetTokens {},
+    GetState {},
+    GetShoe {
+        address: String,
+        token: AssetInfo,
+    },
+    GetBankroll {
+        token: AssetInfo,
+    },
+    GetLiquidity {
+        address: String,
+        token: AssetInfo,
+    },
+    /// Finished rounds of a player, newest first
+    GetHistory {
+        address: String,
+        /// round id to continue after
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetStats {
+        token: AssetInfo,
+    },
+    /// Players of a token ranked from the top, `start_after` an address
+    GetLeaderboard {
+        token: AssetInfo,
+        rank_by: RankBy,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    GetTable {
+        table_id: u64,
+    },
+    GetTables {
+        /// table id to continue after
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetTournament {
+        tournament_id: u64,
+    },
+    GetTournamentEntry {
+        tournament_id: u64,
+        address: String,
+    },
+    /// Registered players ranked by chips
+    GetStandings {
+        tournament_id: u64,
+    },
+    /// Replay a finished game or table round from its entropy and compare the cards.
+    /// Tournament hands are not kept as rounds and cannot be replayed.
+    VerifyRound {
+        address: String,
+        round_id: u64,
+    },
+    GetHouseChain {},
+    /// An anchor or a revealed seed of the house chain
+    GetHouseSeed {
+        index: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RankBy {
+    /// paid out less wagered
+    Profit,
+    /// wagered
+    Volume,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct DepositResponse {
     pub address: String,
+    pub asset: AssetInfo,
     pub deposit: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct GameStateResponce {
     pub state: GameState,
+    pub dealer_score: Score,
+    /// one for each hand of `state.player_hands`
+    pub player_scores: Vec<Score>,
+    /// seconds until the game can be force settled, while in game
+    pub time_left: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub config: Config,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenResponse {
+    pub token: AssetInfo,
+    pub bet_limits: BetLimits,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokensResponse {
+    pub tokens: Vec<TokenResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    pub owner: String,
+    pub pending_owner: Option<String>,
+    pub paused: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ShoeResponse {
+    pub remaining: u32,
+    pub dealt: u32,
+    pub shoe: Shoe,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BankrollResponse {
+    pub token: AssetInfo,
+    pub balance: Uint128,
+    pub liabilities: Uint128,
+    /// left to cover new bets
+    pub available: Uint128,
+    pub total_shares: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidityResponse {
+    pub address: String,
+    pub shares: Uint128,
+    /// current value of the shares in the bankroll
+    pub value: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistoryResponse {
+    pub address: String,
+    pub rounds: Vec<Round>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    pub stats: Stats,
+    /// share of the wagered amount kept by the house, negative when it paid out more
+    pub house_edge: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeaderboardEntry {
+    pub address: String,
+    pub rounds: u64,
+    pub wagered: Uint128,
+    pub paid_out: Uint128,
+    /// signed, paid out less wagered
+    pub profit: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeaderboardResponse {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TableResponse {
+    pub table_id: u64,
+    /// the dealer shows the up card and what the peek found until the dealer plays
+    pub table: Table,
+    pub dealer_score: Score,
+    /// one for each of `table.seats`
+    pub seat_scores: Vec<Score>,
+    /// seconds until the betting closes, or until the seat to act can be made to stand
+    pub time_left: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TablesResponse {
+    pub tables: Vec<TableResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TournamentResponse {
+    pub tournament_id: u64,
+    pub tournament: Tournament,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TournamentEntryResponse {
+    pub address: String,
+    /// the dealer shows the up card and what the peek found while in game
+    pub entry: Entry,
+    /// one for each hand of the game, while there is one
+    pub player_scores: Vec<Score>,
+    pub dealer_score: Option<Score>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Standing {
+    pub address: String,
+    pub chips: Uint128,
+    pub rounds: u32,
+    /// out of chips
+    pub eliminated: bool,
+    /// paid at settlement
+    pub prize: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StandingsResponse {
+    pub tournament_id: u64,
+    pub standings: Vec<Standing>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VerifyRoundResponse {
+    pub round_id: u64,
+    pub entropy: RoundEntropy,
+    /// replayed from `entropy`
+    pub dealer_hand: Hand,
+    pub player_hands: Vec<Hand>,
+    /// the replayed cards are the ones in the history
+    pub verified: bool,
+    /// why the round could not be replayed
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HouseChainResponse {
+    /// none until the owner commits to one
+    pub chain: Option<HouseChain>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HouseSeedResponse {
+    pub index: u64,
+    /// none until revealed
+    pub seed: Option<Binary>,
 }
@@ -0,0 +1,284 @@
+//! Card steps of a round against the dealer, shared by the games, the tournaments,
+//! the tables and the replay in `verify`. Bets are checked and staked by the callers.
+
+use crate::card::Rank;
+use crate::game::{self, Judge};
+use crate::msg::ActionCommand;
+use crate::shoe::Shoe;
+use crate::state::{Config, GameState, PlayerHand, RoundEntropy, Seat, Table, TimeoutAction};
+
+/// Table rules a round is dealt with.
+pub(crate) struct Rules {
+    pub(crate) insurance: bool,
+    pub(crate) dealer_hits_soft_17: bool,
+}
+
+impl Rules {
+    pub(crate) fn of(config: &Config) -> Self {
+        Rules {
+            insurance: config.insurance,
+            dealer_hits_soft_17: config.dealer_hits_soft_17,
+        }
+    }
+
+    /// As they were when the round was dealt.
+    pub(crate) fn of_round(entropy: &RoundEntropy) -> Self {
+        Rules {
+            insurance: entropy.insurance,
+            dealer_hits_soft_17: entropy.dealer_hits_soft_17,
+        }
+    }
+}
+
+/// How the player's hands are closed before they are judged
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Closing {
+    Stand,
+    Surrender,
+    Forfeit,
+    /// the house did not reveal its seed in time
+    HouseTimeout,
+}
+
+impl From<TimeoutAction> for Closing {
+    fn from(action: TimeoutAction) -> Self {
+        match action {
+            TimeoutAction::Stand => Closing::Stand,
+            TimeoutAction::Forfeit => Closing::Forfeit,
+        }
+    }
+}
+
+/// Where the round goes after a step
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Turn {
+    /// the active hand plays on
+    Playing,
+    /// the next hand of a split is up
+    NextHand,
+    /// every hand is closed, see [`dealer_play`]
+    Over(Closing),
+}
+
+/// The dealer's up card and two cards to the first hand, then the dealer peeks.
+/// Dealer blackjack ends the round before the player acts.
+/// When insurance is offered, the dealer peeks after the player decided on it.
+pub(crate) fn deal<T: rand::Rng>(
+    game: &mut GameState,
+    rules: &Rules,
+    shoe: &mut Shoe,
+    rng: &mut T,
+) -> Turn {
+    let (dealer_hand, player_hand) = game::first_deal(shoe, rng);
+    game.dealer_hand = dealer_hand;
+    game.player_hands[0].cards = player_hand;
+
+    if insurance_offered(game, rules) {
+        return Turn::Playing;
+    }
+    game.dealer_blackjack = game::peek_blackjack(&game.dealer_hand, shoe, rng);
+    if game.dealer_blackjack == Some(true) {
+        Turn::Over(Closing::Stand)
+    } else {
+        Turn::Playing
+    }
+}
+
+/// Insurance is offered on the first two cards while the dealer shows an ace.
+pub(crate) fn insurance_offered(game: &GameState, rules: &Rules) -> bool {
+    rules.insurance
+        && matches!(game.dealer_hand[..], [up] if up.rank == Rank::Ace)
+        && game.active_hand == 0
+        && game.player_hands.len() == 1
+        && game.player_hands[0].cards.len() == 2
+        && game.insurance_amount.is_zero()
+}
+
+/// Any action but insurance declines it while offered, then the dealer peeks.
+/// A blackjack ends the round before the action is played.
+pub(crate) fn declined_into_blackjack<T: rand::Rng>(
+    game: &mut GameState,
+    rules: &Rules,
+    action: &ActionCommand,
+    shoe: &Shoe,
+    rng: &mut T,
+) -> bool {
+    if !insurance_offered(game, rules) || matches!(action, ActionCommand::Insurance { amount: _ }) {
+        return false;
+    }
+    game.dealer_blackjack = game::peek_blackjack(&game.dealer_hand, shoe, rng);
+    game.dealer_blackjack == Some(true)
+}
+
+/// Draw for an action on the active hand, then move on to the next hand once it is closed.
+pub(crate) fn act<T: rand::Rng>(
+    game: &mut GameState,
+    action: &ActionCommand,
+    shoe: &mut Shoe,
+    rng: &mut T,
+) -> Turn {
+    let active = game.active_hand as usize;
+    let hand = &mut game.player_hands[active];
+    match *action {
+        ActionCommand::Hit => {
+            hand.cards.push(game::draw_one(shoe, rng));
+            if !matches!(game::judge(&[], &hand.cards), Judge::PlayerBusted(_)) {
+                return Turn::Playing;
+            }
+        }
+        ActionCommand::DoubleDown { amount } => {
+            // raise, draw one, then close hand
+            hand.bet_amount += amount;
+            hand.cards.push(game::draw_one(shoe, rng));
+            game.total_bet_amount += amount;
+        }
+        ActionCommand::Split { amount } => {
+            // split the pair into two hands, then keep playing the current one
+            let pair = hand.cards.pop().expect("pair has two cards");
+            hand.cards.push(game::draw_one(shoe, rng));
+            let split = PlayerHand {
+                cards: vec![pair, game::draw_one(shoe, rng)],
+                bet_amount: amount,
+            };
+            game.player_hands.insert(active + 1, split);
+            game.total_bet_amount += amount;
+            return Turn::Playing;
+        }
+        ActionCommand::Insurance { amount } => {
+            // the dealer peeks, insurance is settled right away
+            game.insurance_amount = amount;
+            game.dealer_blackjack = game::peek_blackjack(&game.dealer_hand, shoe, rng);
+            return if game.dealer_blackjack == Some(true) {
+                Turn::Over(Closing::Stand)
+            } else {
+                Turn::Playing
+            };
+        }
+        ActionCommand::Surrender | ActionCommand::Stand => {}
+    }
+
+    game.active_hand += 1;
+    if game.player_hands.len() > game.active_hand as usize {
+        Turn::NextHand
+    } else if *action == ActionCommand::Surrender {
+        Turn::Over(Closing::Surrender)
+    } else {
+        Turn::Over(Closing::Stand)
+    }
+}
+
+/// Draw the hole card and let the dealer draw, unless every hand busted or none stands.
+pub(crate) fn dealer_play<T: rand::Rng>(
+    game: &mut GameState,
+    rules: &Rules,
+    shoe: &mut Shoe,
+    rng: &mut T,
+    closing: Closing,
+) {
+    if let [up] = game.dealer_hand[..] {
+        let hole = game::draw_hole(up, game.dealer_blackjack, shoe, rng);
+        game.dealer_hand.push(hole);
+    }
+
+    let all_busted = game
+        .player_hands
+        .iter()
+        .all(|h| matches!(game::judge(&[], &h.cards), Judge::PlayerBusted(_)));
+    if !all_busted && closing == Closing::Stand {
+        game.dealer_hand =
+            game::dealer_action(&game.dealer_hand, rules.dealer_hits_soft_17, shoe, rng);
+    }
+}
+
+/// Seats with a bet in the round.
+pub(crate) fn in_play(seat: &Seat) -> bool {
+    !seat.bet_amount.is_zero()
+}
+
+/// Seats with a bet whose hand can still draw.
+pub(crate) fn to_act(seat: &Seat) -> bool {
+    in_play(seat) && game::calc_score(&seat.cards) < 21
+}
+
+/// Two cards to each seat in play and the dealer, then the first seat that can draw is up.
+/// Returns the seat to act, none when a dealer blackjack or the seats' hands end the round.
+pub(crate) fn deal_table<T: rand::Rng>(
+    table: &mut Table,
+    shoe: &mut Shoe,
+    rng: &mut T,
+) -> Option<usize> {
+    let seats = table.seats.iter().filter(|s| in_play(s)).count();
+    let (dealer_hand, hands) = game::table_deal(shoe, rng, seats);
+    for (seat, cards) in table.seats.iter_mut().filter(|s| in_play(s)).zip(hands) {
+        seat.cards = cards;
+    }
+    table.dealer_hand = dealer_hand;
+
+    // dealer blackjack ends the round before any seat acts
+    table.dealer_blackjack = game::peek_blackjack(&table.dealer_hand, shoe, rng);
+    if table.dealer_blackjack == Some(true) {
+        return None;
+    }
+    let first = table.seats.iter().position(to_act)?;
+    table.active_seat = first as u32;
+    Some(first)
+}
+
+/// Draw for the active seat's hit or double down.
+/// Returns the seat to act next, none once the last seat is done.
+pub(crate) fn act_seat<T: rand::Rng>(
+    table: &mut Table,
+    action: &ActionCommand,
+    shoe: &mut Shoe,
+    rng: &mut T,
+) -> Option<usize> {
+    let active = table.active_seat as usize;
+    let seat = &mut table.seats[active];
+    match *action {
+        ActionCommand::Hit => {
+            seat.cards.push(game::draw_one(shoe, rng));
+            // a hit keeps the turn until the hand reaches 21
+            if to_act(seat) {
+                return Some(active);
+            }
+        }
+        ActionCommand::DoubleDown { amount } => {
+            // raise, draw one, then close the hand
+            seat.bet_amount += amount;
+            seat.cards.push(game::draw_one(shoe, rng));
+        }
+        _ => {}
+    }
+    next_seat(table)
+}
+
+/// Pass the turn to the next seat that can draw, none after the last one.
+pub(crate) fn next_seat(table: &mut Table) -> Option<usize> {
+    let after = table.active_seat as usize + 1;
+    let offset = table.seats[after..].iter().position(to_act)?;
+    table.active_seat = (after + offset) as u32;
+    Some(after + offset)
+}
+
+/// Draw the hole card and let the dealer draw once for the whole table,
+/// unless every hand in play is busted or a blackjack.
+pub(crate) fn dealer_play_table<T: rand::Rng>(
+    table: &mut Table,
+    rules: &Rules,
+    shoe: &mut Shoe,
+    rng: &mut T,
+) {
+    if let [up] = table.dealer_hand[..] {
+        let hole = game::draw_hole(up, table.dealer_blackjack, shoe, rng);
+        table.dealer_hand.push(hole);
+    }
+
+    let dealer_plays = table.seats.iter().filter(|s| in_play(s)).any(|s| {
+        !game::is_blackjack(&s.cards)
+            && !matches!(game::judge(&[], &s.cards), Judge::PlayerBusted(_))
+    });
+    if dealer_plays {
+        table.dealer_hand =
+            game::dealer_action(&table.dealer_hand, rules.dealer_hits_soft_17, shoe, rng);
+    }
+}
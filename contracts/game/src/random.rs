@@ -1,14 +1,29 @@
 use cosmwasm_std::Timestamp;
+use rand::{rngs::SmallRng, SeedableRng};
+use sha2::{Digest, Sha256};
 
-use rand::{rngs::SmallRng, RngCore, SeedableRng};
+/// Length of the sha256 digest a player commits to when placing a bet.
+pub const COMMITMENT_LENGTH: usize = 32;
 
-// this is totally idiotic shit.
-// do not copy/use otherwise you will be hacked
-pub fn _gen_random_u32(timestamp: Timestamp) -> u32 {
-    let mut rng = SmallRng::seed_from_u64(timestamp.nanos());
-    rng.next_u32()
+pub fn sha256(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(data).to_vec()
 }
 
-pub fn gen_rng(timestamp: Timestamp) -> SmallRng {
-    SmallRng::seed_from_u64(timestamp.nanos())
+pub fn verify_seed(seed: &[u8], commitment: &[u8]) -> bool {
+    sha256(seed) == commitment
+}
+
+/// Mix player and house entropy into one rng, with the time of the block the move was
+/// made in. Neither party alone can decide the cards drawn from it.
+pub fn gen_rng(player_seed: &[u8], house_seed: &[u8], made_at: Timestamp) -> SmallRng {
+    let digest = Sha256::new()
+        .chain(player_seed)
+        .chain(house_seed)
+        .chain(made_at.nanos().to_be_bytes())
+        .finalize();
+
+    let mut seed = <SmallRng as SeedableRng>::Seed::default();
+    let len = seed.as_ref().len();
+    seed.as_mut().copy_from_slice(&digest[..len]);
+    SmallRng::from_seed(seed)
 }
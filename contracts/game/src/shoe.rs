@@ -0,0 +1,208 @@
+use cosmwasm_std::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::card::{BJCard, CARDLIST};
+
+/// Multi-deck shoe.
+///
+/// Contract storage is public, so the shoe keeps how many of each card are left instead of
+/// a shuffled order. Drawing uniformly from what is left deals the same odds as a shuffled
+/// shoe without revealing the next card.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Shoe {
+    pub decks: u8,
+    /// remaining count of each of the 52 cards, in the order of `CARDLIST`
+    pub remaining: Vec<u32>,
+    /// count of each card dealt in the round in play, none before its first draw
+    #[serde(default)]
+    pub in_play: Vec<u32>,
+}
+
+impl Shoe {
+    pub fn new(decks: u8) -> Self {
+        Self {
+            decks,
+            remaining: vec![decks as u32; CARDLIST.len()],
+            in_play: vec![],
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        52 * self.decks as u32
+    }
+
+    pub fn len(&self) -> u32 {
+        self.remaining.iter().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn dealt(&self) -> u32 {
+        self.size() - self.len()
+    }
+
+    /// The shoe is reshuffled between rounds once this share of it has been dealt.
+    pub fn needs_shuffle(&self, penetration: Decimal) -> bool {
+        Decimal::from_ratio(self.dealt(), self.size()) >= penetration
+    }
+
+    /// The cards of the last round go to the discard tray, out of play.
+    pub fn discard(&mut self) {
+        self.in_play.clear();
+    }
+
+    /// Shuffle the discards back in. The cards of the round in play stay on the table.
+    fn reshuffle(&mut self) {
+        self.remaining = (0..CARDLIST.len())
+            .map(|i| {
+                let in_play = self.in_play.get(i).copied().unwrap_or_default();
+                (self.decks as u32).saturating_sub(in_play)
+            })
+            .collect();
+    }
+
+    /// Remove one card from the shoe. An empty shoe is reshuffled first.
+    pub fn draw<T: rand::Rng>(&mut self, rng: &mut T) -> BJCard {
+        self.draw_where(rng, |_| true)
+    }
+
+    /// The card a draw would give, left in the shoe.
+    pub fn peek<T: rand::Rng>(&self, rng: &mut T) -> BJCard {
+        self.clone().draw(rng)
+    }
+
+    /// Remove one of the cards matching `pred`, each as likely as the others.
+    /// The shoe is reshuffled first when none of them is left.
+    pub fn draw_where<T: rand::Rng, P: Fn(&BJCard) -> bool>(
+        &mut self,
+        rng: &mut T,
+        pred: P,
+    ) -> BJCard {
+        let matching = |shoe: &Shoe| -> u32 {
+            CARDLIST
+                .iter()
+                .zip(shoe.remaining.iter())
+                .filter(|(card, _)| pred(card))
+                .map(|(_, count)| count)
+                .sum()
+        };
+        if matching(self) == 0 {
+            self.reshuffle();
+        }
+        if matching(self) == 0 {
+            // every card that fits is on the table
+            self.remaining = vec![self.decks as u32; CARDLIST.len()];
+        }
+
+        if self.in_play.is_empty() {
+            self.in_play = vec![0; CARDLIST.len()];
+        }
+        let mut nth = rng.gen_range(0..matching(self));
+        for (i, count) in self.remaining.iter_mut().enumerate() {
+            if !pred(&CARDLIST[i]) {
+                continue;
+            }
+            if nth < *count {
+                *count -= 1;
+                self.in_play[i] += 1;
+                return CARDLIST[i];
+            }
+            nth -= *count;
+        }
+
+        unreachable!("card index is within the shoe")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{prelude::SmallRng, SeedableRng};
+
+    use super::*;
+    use crate::card::Rank;
+
+    #[test]
+    fn draw_removes_card() {
+        let mut rng = SmallRng::seed_from_u64(0_u64);
+        let mut shoe = Shoe::new(1);
+        assert_eq!(52, shoe.len());
+
+        let mut drawn = vec![];
+        for _ in 0..52 {
+            drawn.push(shoe.draw(&mut rng));
+        }
+        assert!(shoe.is_empty());
+        assert_eq!(52, shoe.dealt());
+
+        // every card of the deck exactly once
+        for card in CARDLIST.iter() {
+            assert_eq!(1, drawn.iter().filter(|c| *c == card).count());
+        }
+
+        // reshuffled when empty
+        shoe.discard();
+        let _ = shoe.draw(&mut rng);
+        assert_eq!(51, shoe.len());
+    }
+
+    #[test]
+    fn peek_and_draw_where() {
+        let mut shoe = Shoe::new(1);
+        let peeked = shoe.peek(&mut SmallRng::seed_from_u64(0_u64));
+        assert_eq!(52, shoe.len());
+        assert_eq!(peeked, shoe.draw(&mut SmallRng::seed_from_u64(0_u64)));
+
+        let mut rng = SmallRng::seed_from_u64(0_u64);
+        let mut shoe = Shoe::new(1);
+        for _ in 0..4 {
+            assert_eq!(
+                Rank::Ace,
+                shoe.draw_where(&mut rng, |c| c.rank == Rank::Ace).rank
+            );
+        }
+        assert_eq!(48, shoe.len());
+
+        // reshuffled when no match is left
+        shoe.discard();
+        let card = shoe.draw_where(&mut rng, |c| c.rank == Rank::Ace);
+        assert_eq!(Rank::Ace, card.rank);
+        assert_eq!(51, shoe.len());
+    }
+
+    #[test]
+    fn reshuffle_mid_round() {
+        let mut rng = SmallRng::seed_from_u64(0_u64);
+        let mut shoe = Shoe::new(1);
+        for _ in 0..50 {
+            let _ = shoe.draw(&mut rng);
+        }
+        shoe.discard();
+
+        // the round runs the shoe dry and goes on without the cards on the table
+        let on_table: Vec<BJCard> = (0..3).map(|_| shoe.draw(&mut rng)).collect();
+        assert_eq!(49, shoe.len());
+        for card in on_table.iter() {
+            let i = CARDLIST.iter().position(|c| c == card).unwrap();
+            assert_eq!(0, shoe.remaining[i]);
+            assert_eq!(1, shoe.in_play[i]);
+        }
+    }
+
+    #[test]
+    fn penetration() {
+        let mut rng = SmallRng::seed_from_u64(0_u64);
+        let mut shoe = Shoe::new(2);
+        let penetration = Decimal::percent(75);
+
+        for _ in 0..77 {
+            let _ = shoe.draw(&mut rng);
+        }
+        assert!(!shoe.needs_shuffle(penetration));
+
+        let _ = shoe.draw(&mut rng);
+        assert!(shoe.needs_shuffle(penetration));
+    }
+}
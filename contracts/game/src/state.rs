@@ -1,38 +1,671 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Binary, Decimal, Timestamp, Uint128};
+use cw_storage_plus::{
+    Index, IndexList, IndexedMap, Item, Map, MultiIndex, Prefixer, U128Key, U64Key,
+};
 
 use crate::card::Hand;
+use crate::game::{GameResult, Judge};
+use crate::msg::ActionCommand;
+use crate::shoe::Shoe;
+
+pub use bj_common::AssetInfo;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub owner: Addr,
+    /// proposed owner, until accepted
+    pub pending_owner: Option<Addr>,
+    /// no new bets while paused. games in progress can still be finished
+    pub paused: bool,
 }
 
 pub const STATE: Item<State> = Item::new("state");
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
-    pub token_address: Addr,
+    pub insurance: bool,
+    pub payout_table: PayoutTable,
+    pub decks: u8,
+    /// share of the shoe dealt before it is reshuffled
+    pub penetration: Decimal,
+    /// H17 when true, S17 otherwise
+    pub dealer_hits_soft_17: bool,
+    pub perfect_pairs: PerfectPairsTable,
+    pub twenty_one_plus_three: TwentyOnePlusThreeTable,
+    /// seconds the player or the house has to move before anyone can force settle the game
+    pub timeout: u64,
+    pub timeout_action: TimeoutAction,
+}
+
+/// How a timed out game is resolved.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutAction {
+    /// the remaining hands stand and the dealer plays
+    Stand,
+    /// all hands lose
+    Forfeit,
+}
+
+impl Default for TimeoutAction {
+    fn default() -> Self {
+        TimeoutAction::Stand
+    }
+}
+
+/// Amount returned per unit bet, stake included.
+/// e.g. a 3:2 blackjack returns 2.5 and a surrender returns 0.5.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PayoutTable {
+    pub blackjack: Decimal,
+    pub win: Decimal,
+    pub push: Decimal,
+    pub insurance: Decimal,
+    pub surrender: Decimal,
+}
+
+impl Default for PayoutTable {
+    fn default() -> Self {
+        Self {
+            blackjack: Decimal::from_ratio(5u128, 2u128),
+            win: Decimal::from_ratio(2u128, 1u128),
+            push: Decimal::one(),
+            insurance: Decimal::from_ratio(3u128, 1u128),
+            surrender: Decimal::from_ratio(1u128, 2u128),
+        }
+    }
+}
+
+impl PayoutTable {
+    /// Most a bet can return on one hand, doubled down or blackjack.
+    pub fn max_payout(&self, bet: Uint128) -> Uint128 {
+        std::cmp::max(bet * self.blackjack, (bet + bet) * self.win)
+    }
+
+    /// Amount returned on a hand's bet.
+    pub fn payout(&self, bet: Uint128, result: GameResult) -> Uint128 {
+        bet * match result {
+            GameResult::Blackjack => self.blackjack,
+            GameResult::Win => self.win,
+            GameResult::Loose => Decimal::zero(),
+            GameResult::Draw => self.push,
+            GameResult::Surrender => self.surrender,
+        }
+    }
+
+    /// winning bets must pay more than the stake, returned bets must not.
+    pub fn is_valid(&self) -> bool {
+        self.blackjack > Decimal::one()
+            && self.win > Decimal::one()
+            && self.insurance > Decimal::one()
+            && self.push <= Decimal::one()
+            && self.surrender <= Decimal::one()
+    }
+}
+
+/// Perfect Pairs side bet, amount returned per unit bet, stake included.
+/// e.g. a 25:1 perfect pair returns 26.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PerfectPairsTable {
+    /// same rank, different colors
+    pub mixed: Decimal,
+    /// same rank and color, different suits
+    pub colored: Decimal,
+    /// same rank and suit
+    pub perfect: Decimal,
+}
+
+impl Default for PerfectPairsTable {
+    fn default() -> Self {
+        Self {
+            mixed: Decimal::from_ratio(7u128, 1u128),
+            colored: Decimal::from_ratio(13u128, 1u128),
+            perfect: Decimal::from_ratio(26u128, 1u128),
+        }
+    }
+}
+
+impl PerfectPairsTable {
+    pub fn max_payout(&self, bet: Uint128) -> Uint128 {
+        [self.mixed, self.colored, self.perfect]
+            .iter()
+            .map(|m| bet * *m)
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.mixed > Decimal::one()
+            && self.colored > Decimal::one()
+            && self.perfect > Decimal::one()
+    }
+}
+
+/// 21+3 side bet, amount returned per unit bet, stake included.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TwentyOnePlusThreeTable {
+    pub flush: Decimal,
+    pub straight: Decimal,
+    pub three_of_a_kind: Decimal,
+    pub straight_flush: Decimal,
+    pub suited_trips: Decimal,
+}
+
+impl Default for TwentyOnePlusThreeTable {
+    fn default() -> Self {
+        Self {
+            flush: Decimal::from_ratio(6u128, 1u128),
+            straight: Decimal::from_ratio(11u128, 1u128),
+            three_of_a_kind: Decimal::from_ratio(31u128, 1u128),
+            straight_flush: Decimal::from_ratio(41u128, 1u128),
+            suited_trips: Decimal::from_ratio(101u128, 1u128),
+        }
+    }
+}
+
+impl TwentyOnePlusThreeTable {
+    pub fn max_payout(&self, bet: Uint128) -> Uint128 {
+        [
+            self.flush,
+            self.straight,
+            self.three_of_a_kind,
+            self.straight_flush,
+            self.suited_trips,
+        ]
+        .iter()
+        .map(|m| bet * *m)
+        .max()
+        .unwrap_or_default()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.flush > Decimal::one()
+            && self.straight > Decimal::one()
+            && self.three_of_a_kind > Decimal::one()
+            && self.straight_flush > Decimal::one()
+            && self.suited_trips > Decimal::one()
+    }
+}
+
+/// Table limits for the main bet. Also applied to double down and split top-ups.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BetLimits {
+    pub min_bet: Uint128,
+    pub max_bet: Option<Uint128>,
+    /// share of the available bankroll a single bet may reach
+    pub max_bankroll_ratio: Option<Decimal>,
+}
+
+impl Default for BetLimits {
+    fn default() -> Self {
+        Self {
+            min_bet: Uint128::new(1),
+            max_bet: None,
+            max_bankroll_ratio: None,
+        }
+    }
+}
+
+impl BetLimits {
+    pub fn is_valid(&self) -> bool {
+        !self.min_bet.is_zero()
+            && !matches!(self.max_bet, Some(max) if max < self.min_bet)
+            && !matches!(self.max_bankroll_ratio, Some(r) if r.is_zero() || r > Decimal::one())
+    }
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// A whitelisted cw20 or bank denom. Each one is played against its own bankroll.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct TokenConfig {
+    pub bet_limits: BetLimits,
+}
+
+/// Assets players can deposit and bet in
+pub const TOKENS: Map<&AssetInfo, TokenConfig> = Map::new("tokens");
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Vault {
     pub balance: Uint128,
 }
 
-pub const VAULT: Map<&Addr, Vault> = Map::new("vault");
+/// Balances of each player per asset
+pub const VAULT: Map<(&Addr, &AssetInfo), Vault> = Map::new("vault");
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+/// House funds, kept apart from the players' vaults and owned by liquidity providers.
+/// Stakes flow in when placed and payouts are paid out of it.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Bankroll {
+    pub balance: Uint128,
+    /// worst-case payouts reserved for games in progress
+    pub liabilities: Uint128,
+    /// LP shares issued against `balance`
+    pub total_shares: Uint128,
+    /// times the balance ran out with shares issued, leaving every one of them worthless
+    #[serde(default)]
+    pub wipeouts: u32,
+}
+
+impl Bankroll {
+    pub fn available(&self) -> Uint128 {
+        self.balance.saturating_sub(self.liabilities)
+    }
+}
+
+/// Bankroll of each token, kept after the token is delisted until it is withdrawn
+pub const BANKROLL: Map<&AssetInfo, Bankroll> = Map::new("bankroll");
+
+/// LP shares of each liquidity provider per token
+pub const LIQUIDITY: Map<(&Addr, &AssetInfo), Uint128> = Map::new("liquidity");
+
+/// Wipeouts of the bankroll when each position was last added to.
+/// Shares from before a later wipeout are worth nothing.
+pub const LIQUIDITY_WIPEOUTS: Map<(&Addr, &AssetInfo), u32> = Map::new("liquidity_wipeouts");
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, JsonSchema)]
+pub struct PlayerHand {
+    pub cards: Hand,
+    pub bet_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, JsonSchema)]
 pub struct GameState {
     pub ingame: bool,
+    /// sum of the bets on all hands
     pub total_bet_amount: Uint128,
     pub dealer_hand: Hand,
-    pub player_hand: Hand,
+    /// what the dealer's peek found, the hole card is drawn to match when the dealer plays.
+    /// It stands in for a hole card dealt face down: none is kept before then, so no
+    /// query can leak it.
+    pub dealer_blackjack: Option<bool>,
+    /// more than one hand after a split, played in order
+    pub player_hands: Vec<PlayerHand>,
+    pub active_hand: u32,
+    pub insurance_amount: Uint128,
+    /// Perfect Pairs side bet, settled at the first deal
+    pub perfect_pairs_amount: Uint128,
+    /// 21+3 side bet, settled at the first deal
+    pub twenty_one_plus_three_amount: Uint128,
+    /// paid on the side bets at the first deal
+    pub side_payout: Uint128,
+    /// worst-case payout reserved from the bankroll
+    pub liability: Uint128,
+    pub seed_commitment: Binary,
+    pub player_seed: Option<Binary>,
+    /// the move waiting for its house seed
+    pub pending: Option<PendingMove>,
+    /// block the bet was placed in
+    pub started_height: u64,
+    pub started_at: Timestamp,
+    /// the last move was made or drawn, see [`Config::timeout`]
+    pub turn_started: Timestamp,
+    /// the payout is sent to the player at settlement instead of the vault
+    pub auto_withdraw: bool,
+}
+
+/// Games of each player per token
+pub const GAMESTATE: Map<(&Addr, &AssetInfo), GameState> = Map::new("gamestate");
+
+/// Shoe of each player per token, dealt from until the cut card
+pub const SHOE: Map<(&Addr, &AssetInfo), Shoe> = Map::new("shoe");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HandResult {
+    pub result: GameResult,
+    pub judge: Judge,
+}
+
+/// A finished round, kept in the player's history.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Round {
+    pub round_id: u64,
+    /// asset the round was played in
+    pub token: AssetInfo,
+    pub dealer_hand: Hand,
+    pub player_hands: Vec<PlayerHand>,
+    /// one for each of `player_hands`
+    pub results: Vec<HandResult>,
+    pub insurance: Option<GameResult>,
+    /// main bets, doubles and splits included
+    pub bet: Uint128,
+    /// insurance and side bets
+    pub side_bets: Uint128,
+    /// everything returned to the player, stakes included
+    pub payout: Uint128,
+    /// block the round was settled in
+    pub height: u64,
+}
+
+/// Id of the last finished round, shared by all players
+pub const ROUND_COUNT: Item<u64> = Item::new("round_count");
+
+pub const HISTORY: Map<(&Addr, U64Key), Round> = Map::new("history");
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ResultCounts {
+    pub blackjack: u64,
+    pub win: u64,
+    pub loose: u64,
+    pub draw: u64,
+    pub surrender: u64,
+}
+
+impl ResultCounts {
+    pub fn add(&mut self, result: GameResult) {
+        let count = match result {
+            GameResult::Blackjack => &mut self.blackjack,
+            GameResult::Win => &mut self.win,
+            GameResult::Loose => &mut self.loose,
+            GameResult::Draw => &mut self.draw,
+            GameResult::Surrender => &mut self.surrender,
+        };
+        *count += 1;
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct JudgeCounts {
+    pub dealer_busted: u64,
+    pub player_busted: u64,
+    pub dealer_win: u64,
+    pub player_win: u64,
+    pub player_bj_win: u64,
+    pub draw: u64,
+    pub surrender: u64,
+    pub forfeit: u64,
+    pub house_timeout: u64,
+}
+
+impl JudgeCounts {
+    pub fn add(&mut self, judge: Judge) {
+        let count = match judge {
+            Judge::DealerBusted(_) => &mut self.dealer_busted,
+            Judge::PlayerBusted(_) => &mut self.player_busted,
+            Judge::DealerWin(_, _) => &mut self.dealer_win,
+            Judge::PlayerWin(_, _) => &mut self.player_win,
+            Judge::PlayerBJWin(_, _) => &mut self.player_bj_win,
+            Judge::Draw(_, _) => &mut self.draw,
+            Judge::Surrender(_) => &mut self.surrender,
+            Judge::Forfeit(_) => &mut self.forfeit,
+            Judge::HouseTimeout(_) => &mut self.house_timeout,
+        };
+        *count += 1;
+    }
+}
+
+/// Totals over all finished rounds in a token.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Stats {
+    pub rounds: u64,
+    /// all stakes, side bets included
+    pub wagered: Uint128,
+    pub paid_out: Uint128,
+    /// counted per hand
+    pub results: ResultCounts,
+    pub judges: JudgeCounts,
+}
+
+pub const STATS: Map<&AssetInfo, Stats> = Map::new("stats");
+
+/// Totals of one player in a token, ranked on the token's leaderboard.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PlayerStats {
+    pub rounds: u64,
+    pub wagered: Uint128,
+    pub paid_out: Uint128,
+}
+
+impl PlayerStats {
+    /// Net profit shifted by 2^127, so that losses sort below gains.
+    pub fn profit_key(&self) -> u128 {
+        (1u128 << 127)
+            .wrapping_add(self.paid_out.u128())
+            .wrapping_sub(self.wagered.u128())
+    }
+}
+
+/// Splits a `player_stats` key into the token, as its [`stats_prefix`], and the player.
+pub fn split_stats_key(pk: &[u8]) -> (&[u8], &[u8]) {
+    // the token takes two length-prefixed steps, its kind and its id
+    let step = |at: usize| at + 2 + u16::from_be_bytes([pk[at], pk[at + 1]]) as usize;
+    pk.split_at(step(step(0)))
+}
+
+/// Prefix of the `player_stats` keys and leaderboards of a token.
+pub fn stats_prefix(token: &AssetInfo) -> Vec<u8> {
+    token
+        .prefix()
+        .iter()
+        .flat_map(|step| [&(step.len() as u16).to_be_bytes()[..], step].concat())
+        .collect()
+}
+
+/// Both rank the players of a token, which prefixes the index key.
+pub struct PlayerStatsIndexes<'a> {
+    pub profit: MultiIndex<'a, (Vec<u8>, U128Key, Vec<u8>), PlayerStats>,
+    pub volume: MultiIndex<'a, (Vec<u8>, U128Key, Vec<u8>), PlayerStats>,
+}
+
+impl<'a> IndexList<PlayerStats> for PlayerStatsIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<PlayerStats>> + '_> {
+        let v: Vec<&dyn Index<PlayerStats>> = vec![&self.profit, &self.volume];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Keyed by token and player
+pub fn player_stats<'a>(
+) -> IndexedMap<'a, (&'a AssetInfo, &'a Addr), PlayerStats, PlayerStatsIndexes<'a>> {
+    let indexes = PlayerStatsIndexes {
+        profit: MultiIndex::new(
+            |s, pk| {
+                let token = split_stats_key(&pk).0.to_vec();
+                (token, U128Key::new(s.profit_key()), pk)
+            },
+            "player_stats",
+            "player_stats__profit",
+        ),
+        volume: MultiIndex::new(
+            |s, pk| {
+                let token = split_stats_key(&pk).0.to_vec();
+                (token, U128Key::new(s.wagered.u128()), pk)
+            },
+            "player_stats",
+            "player_stats__volume",
+        ),
+    };
+    IndexedMap::new("player_stats", indexes)
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TablePhase {
+    /// seated players place their bets until the window closes
+    Betting,
+    /// seats act in order, then the dealer plays
+    Playing,
+}
+
+/// A player at a table. Seats without a bet sit the round out.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Seat {
+    pub player: Addr,
+    pub bet_amount: Uint128,
+    pub cards: Hand,
+    pub seed_commitment: Binary,
+    pub player_seed: Option<Binary>,
+    /// worst-case payout reserved from the bankroll
+    pub liability: Uint128,
+}
+
+/// Up to seven seats playing against one dealer hand from one shoe.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Table {
+    /// asset the table plays in
+    pub token: AssetInfo,
+    pub seats: Vec<Seat>,
+    pub phase: TablePhase,
+    /// seconds the betting stays open after the first bet
+    pub betting_window: u64,
+    /// seconds a seat has to act before anyone can make it stand
+    pub turn_timeout: u64,
+    pub betting_ends: Option<Timestamp>,
+    pub dealer_hand: Hand,
+    /// what the dealer's peek found, the hole card is drawn to match when the dealer plays
+    pub dealer_blackjack: Option<bool>,
+    /// seat to act while playing
+    pub active_seat: u32,
+    pub turn_started: Timestamp,
+    /// the deal or move waiting for its house seed
+    pub pending: Option<PendingMove>,
+}
+
+/// Id of the last opened table
+pub const TABLE_COUNT: Item<u64> = Item::new("table_count");
+
+pub const TABLES: Map<U64Key, Table> = Map::new("tables");
+
+pub const TABLE_SHOE: Map<U64Key, Shoe> = Map::new("table_shoe");
+
+/// Players buy in for equal chip stacks, kept apart from their vaults,
+/// and split the buy-ins by final chip count.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Tournament {
+    /// asset of the buy-in and the prizes
+    pub token: AssetInfo,
+    pub buy_in: Uint128,
+    pub starting_chips: Uint128,
+    /// registration closes and play starts
+    pub starts_at: Timestamp,
+    pub ends_at: Option<Timestamp>,
+    /// rounds each player gets
+    pub rounds: Option<u32>,
+    /// share of the prize pool for each place, from the first
+    pub payouts: Vec<Decimal>,
+    pub prize_pool: Uint128,
+    pub entries: u32,
+    pub settled: bool,
 }
 
-pub const GAMESTATE: Map<&Addr, GameState> = Map::new("gamestate");
+/// A registered player
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Entry {
+    /// registration order, which breaks ties in the standings
+    pub number: u32,
+    pub chips: Uint128,
+    /// rounds finished
+    pub rounds: u32,
+    /// the last round played, still in game until it is settled
+    pub game: Option<GameState>,
+    pub prize: Uint128,
+}
+
+/// Id of the last created tournament
+pub const TOURNAMENT_COUNT: Item<u64> = Item::new("tournament_count");
+
+pub const TOURNAMENTS: Map<U64Key, Tournament> = Map::new("tournaments");
+
+pub const ENTRIES: Map<(U64Key, &Addr), Entry> = Map::new("entries");
+
+pub const TOURNAMENT_SHOE: Map<(U64Key, &Addr), Shoe> = Map::new("tournament_shoe");
+
+/// What a house seed of a round was drawn for
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Move {
+    Deal,
+    Action(ActionCommand),
+    ForceSettle(TimeoutAction),
+}
+
+/// A house seed of the chain and the move it was drawn for
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HouseSeed {
+    pub made_for: Move,
+    pub index: u64,
+    pub seed: Binary,
+    /// time of the block the move was made in, mixed in with the seeds
+    pub made_at: Timestamp,
+}
+
+/// A move made before the house seed it is drawn from was revealed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingMove {
+    pub made_for: Move,
+    /// index of the house seed in the chain
+    pub index: u64,
+    /// key of the move in `SEED_REQUESTS`
+    pub request: u64,
+    pub made_at: Timestamp,
+}
+
+/// House seeds are committed ahead as a hash chain: each seed is the sha256 preimage
+/// of the one before it, back to an anchor the owner commits to. Every move made before
+/// a seed is revealed waits on it, however many other moves do, so its seed is fixed
+/// before the move and unknown until after.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HouseChain {
+    /// index of the last anchor committed
+    pub anchor: u64,
+    /// index of the seed revealed next, which the moves made until then wait on
+    pub next: u64,
+}
+
+pub const HOUSE_CHAIN: Item<HouseChain> = Item::new("house_chain");
+
+/// Anchors and revealed seeds of the house chain by index
+pub const HOUSE_SEEDS: Map<U64Key, Binary> = Map::new("house_seeds");
+
+/// What a house seed is waiting to be revealed for
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedRequest {
+    Game { player: Addr, token: AssetInfo },
+    Table { table_id: u64 },
+    Tournament { tournament_id: u64, player: Addr },
+}
+
+/// Moves waiting on a house seed, by its index and the key of the move
+pub const SEED_REQUESTS: Map<(U64Key, U64Key), SeedRequest> = Map::new("seed_requests");
+
+/// Key of the last move that waited on a house seed
+pub const SEED_REQUEST_COUNT: Item<u64> = Item::new("seed_request_count");
+
+/// A player's seed in a round
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlayerSeed {
+    pub player: Addr,
+    pub seed_commitment: Binary,
+    /// revealed with the player's first action
+    pub seed: Option<Binary>,
+}
+
+/// Everything the cards of a round were drawn from, to replay it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoundEntropy {
+    /// the shoe before the deal
+    pub shoe: Shoe,
+    /// the player of a game, or the seats in play at a table in seat order
+    pub players: Vec<PlayerSeed>,
+    /// table the round was dealt at
+    pub table_id: Option<u64>,
+    /// rules at the bet
+    pub insurance: bool,
+    pub dealer_hits_soft_17: bool,
+    /// in the order they were drawn
+    pub house_seeds: Vec<HouseSeed>,
+}
+
+/// Entropy of the games in progress, keyed as `GAMESTATE`
+pub const GAME_ENTROPY: Map<(&Addr, &AssetInfo), RoundEntropy> = Map::new("game_entropy");
+
+/// Entropy of the table rounds in progress, keyed as `TABLES`
+pub const TABLE_ENTROPY: Map<U64Key, RoundEntropy> = Map::new("table_entropy");
+
+/// Entropy of finished rounds, keyed as `HISTORY`.
+/// Tournament hands are not kept as rounds and have none.
+pub const ROUND_ENTROPY: Map<(&Addr, U64Key), RoundEntropy> = Map::new("round_entropy");
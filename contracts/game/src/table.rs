@@ -0,0 +1,965 @@
+use cosmwasm_std::{
+    Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage, Uint128,
+};
+use cw_storage_plus::{Bound, U64Key};
+
+use crate::card::{hand_to_string, Hand};
+use crate::contract::{
+    check_bet_limits, exec_bet, next_round_id, only_owner, record_round, release, reserve,
+    reveal_seed, whitelisted,
+};
+use crate::error::ContractError;
+use crate::game::{self, GameResult, Judge};
+use crate::msg::{ActionCommand, TableResponse, TablesResponse};
+use crate::play::{self, in_play, Rules};
+use crate::shoe::Shoe;
+use crate::state::{
+    AssetInfo, Config, HandResult, HouseSeed, Move, PlayerHand, PlayerSeed, Round, RoundEntropy,
+    Seat, SeedRequest, Table, TablePhase, TimeoutAction, CONFIG, ROUND_ENTROPY, STATE, TABLES,
+    TABLE_COUNT, TABLE_ENTROPY, TABLE_SHOE,
+};
+use crate::{house, random};
+
+pub const MAX_SEATS: usize = 7;
+
+/// Default seconds the betting stays open after the first bet
+pub const DEFAULT_BETTING_WINDOW: u64 = 60;
+
+/// Default seconds a seat has to act
+pub const DEFAULT_TURN_TIMEOUT: u64 = 300;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+fn load_table(deps: &DepsMut, table_id: u64) -> Result<Table, ContractError> {
+    Ok(TABLES.load(deps.storage, U64Key::new(table_id))?)
+}
+
+pub fn try_open_table(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token: AssetInfo,
+    betting_window: Option<u64>,
+    turn_timeout: Option<u64>,
+) -> Result<Response, ContractError> {
+    only_owner(deps.storage, &info)?;
+
+    whitelisted(deps.storage, &token)?;
+
+    let betting_window = betting_window.unwrap_or(DEFAULT_BETTING_WINDOW);
+    let turn_timeout = turn_timeout.unwrap_or(DEFAULT_TURN_TIMEOUT);
+    if betting_window == 0 || turn_timeout == 0 {
+        return Err(ContractError::InvalidTimeout {});
+    }
+
+    let table_id = TABLE_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    TABLE_COUNT.save(deps.storage, &table_id)?;
+    let table = Table {
+        token: token.clone(),
+        seats: vec![],
+        phase: TablePhase::Betting,
+        betting_window,
+        turn_timeout,
+        betting_ends: None,
+        dealer_hand: vec![],
+        dealer_blackjack: None,
+        active_seat: 0,
+        turn_started: env.block.time,
+        pending: None,
+    };
+    TABLES.save(deps.storage, U64Key::new(table_id), &table)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "open_table")
+        .add_attribute("table_id", table_id.to_string())
+        .add_attribute("token", token.to_string()))
+}
+
+pub fn try_join_table(
+    deps: DepsMut,
+    info: MessageInfo,
+    table_id: u64,
+) -> Result<Response, ContractError> {
+    let mut table = load_table(&deps, table_id)?;
+    if table.seats.iter().any(|s| s.player == info.sender) {
+        return Err(ContractError::AlreadySeated {});
+    }
+    if table.seats.len() >= MAX_SEATS {
+        return Err(ContractError::TableFull {});
+    }
+
+    table.seats.push(Seat {
+        player: info.sender.clone(),
+        bet_amount: Uint128::zero(),
+        cards: vec![],
+        seed_commitment: Binary::default(),
+        player_seed: None,
+        liability: Uint128::zero(),
+    });
+    TABLES.save(deps.storage, U64Key::new(table_id), &table)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "join_table")
+        .add_attribute("table_id", table_id.to_string())
+        .add_attribute("seat", (table.seats.len() - 1).to_string()))
+}
+
+pub fn try_leave_table(
+    deps: DepsMut,
+    info: MessageInfo,
+    table_id: u64,
+) -> Result<Response, ContractError> {
+    let mut table = load_table(&deps, table_id)?;
+    let seat = seat_of(&table, &info)?;
+    if table.phase != TablePhase::Betting {
+        return Err(ContractError::TableInPlay {});
+    }
+    if !table.seats[seat].bet_amount.is_zero() {
+        return Err(ContractError::AlreadyBet {});
+    }
+
+    table.seats.remove(seat);
+    if table.seats.iter().all(|s| s.bet_amount.is_zero()) {
+        table.betting_ends = None;
+    }
+    TABLES.save(deps.storage, U64Key::new(table_id), &table)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "leave_table")
+        .add_attribute("table_id", table_id.to_string()))
+}
+
+fn seat_of(table: &Table, info: &MessageInfo) -> Result<usize, ContractError> {
+    table
+        .seats
+        .iter()
+        .position(|s| s.player == info.sender)
+        .ok_or(ContractError::NotSeated {})
+}
+
+/// The stake moves to the bankroll, which reserves a doubled down win for it.
+pub fn try_table_bet(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    table_id: u64,
+    amount: Uint128,
+    seed_commitment: Binary,
+) -> Result<Response, ContractError> {
+    if STATE.load(deps.storage)?.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    let mut table = load_table(&deps, table_id)?;
+    let seat = seat_of(&table, &info)?;
+    if table.phase != TablePhase::Betting
+        || matches!(table.betting_ends, Some(ends) if env.block.time >= ends)
+    {
+        return Err(ContractError::BettingClosed {});
+    }
+    if !table.seats[seat].bet_amount.is_zero() {
+        return Err(ContractError::AlreadyBet {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let token = table.token.clone();
+    let limits = whitelisted(deps.storage, &token)?.bet_limits;
+    check_bet_limits(deps.storage, &token, &limits, amount)?;
+
+    if seed_commitment.len() != random::COMMITMENT_LENGTH {
+        return Err(ContractError::InvalidCommitment {});
+    }
+
+    let liability = config.payout_table.max_payout(amount);
+    reserve(deps.storage, &token, amount, liability)?;
+    let balance_after = exec_bet(deps.storage, &info, &token, amount)?;
+
+    table.seats[seat].bet_amount = amount;
+    table.seats[seat].seed_commitment = seed_commitment;
+    table.seats[seat].liability = liability;
+    let betting_ends = *table
+        .betting_ends
+        .get_or_insert(env.block.time.plus_seconds(table.betting_window));
+    TABLES.save(deps.storage, U64Key::new(table_id), &table)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "table_bet")
+        .add_attribute("table_id", table_id.to_string())
+        .add_attribute("bet_amount", amount)
+        .add_attribute("balance_after", balance_after.balance)
+        .add_attribute("betting_ends", betting_ends.seconds().to_string()))
+}
+
+/// Seeds of the seats in the round, mixed into every draw of the table.
+/// Until a player reveals the seed, the commitment stands in for it.
+pub(crate) fn table_seed(table: &Table) -> Vec<u8> {
+    table
+        .seats
+        .iter()
+        .filter(|s| in_play(s))
+        .flat_map(|s| {
+            s.player_seed
+                .as_ref()
+                .unwrap_or(&s.seed_commitment)
+                .to_vec()
+        })
+        .collect()
+}
+
+/// The first round deals from a fresh shoe, reshuffled between rounds at the cut card.
+/// The cards are dealt once the house reveals the seed the deal waits on.
+pub fn try_deal_table(deps: DepsMut, env: Env, table_id: u64) -> Result<Response, ContractError> {
+    let mut table = load_table(&deps, table_id)?;
+    if table.phase != TablePhase::Betting {
+        return Err(ContractError::TableInPlay {});
+    }
+    if !table.seats.iter().any(in_play) {
+        return Err(ContractError::ActionBeforeBet {});
+    }
+    if !table.seats.iter().all(in_play) {
+        let seconds_left = betting_left(&table, &env).unwrap_or_default();
+        if seconds_left > 0 {
+            return Err(ContractError::BettingOpen { seconds_left });
+        }
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let mut shoe = match TABLE_SHOE.may_load(deps.storage, U64Key::new(table_id))? {
+        Some(shoe) if shoe.decks == config.decks && !shoe.needs_shuffle(config.penetration) => shoe,
+        _ => Shoe::new(config.decks),
+    };
+    shoe.discard();
+
+    let entropy = RoundEntropy {
+        shoe: shoe.clone(),
+        players: table
+            .seats
+            .iter()
+            .filter(|s| in_play(s))
+            .map(|s| PlayerSeed {
+                player: s.player.clone(),
+                seed_commitment: s.seed_commitment.clone(),
+                seed: None,
+            })
+            .collect(),
+        table_id: Some(table_id),
+        insurance: false,
+        dealer_hits_soft_17: config.dealer_hits_soft_17,
+        house_seeds: vec![],
+    };
+    TABLE_ENTROPY.save(deps.storage, U64Key::new(table_id), &entropy)?;
+
+    table.pending = Some(house::request_seed(
+        deps.storage,
+        &env,
+        Move::Deal,
+        &SeedRequest::Table { table_id },
+    )?);
+    table.phase = TablePhase::Playing;
+    table.betting_ends = None;
+    table.turn_started = env.block.time;
+    TABLES.save(deps.storage, U64Key::new(table_id), &table)?;
+    TABLE_SHOE.save(deps.storage, U64Key::new(table_id), &shoe)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "deal_table")
+        .add_attribute("table_id", table_id.to_string()))
+}
+
+/// The seed committed at bet must be revealed with the seat's first action.
+/// The action is checked and staked here, its card is drawn once the house reveals
+/// the seed it waits on.
+pub fn try_table_action(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    table_id: u64,
+    action: ActionCommand,
+    seed: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let mut table = load_table(&deps, table_id)?;
+    if table.phase != TablePhase::Playing {
+        return Err(ContractError::ActionBeforeBet {});
+    }
+    if table.pending.is_some() {
+        return Err(ContractError::SeedPending {});
+    }
+    let active = table.active_seat as usize;
+    if table.seats[active].player != info.sender {
+        return Err(ContractError::NotYourTurn {});
+    }
+
+    let seat = &table.seats[active];
+    let player_seed = reveal_seed(seat.player_seed.clone(), seed, &seat.seed_commitment)?;
+    table.seats[active].player_seed = Some(player_seed);
+
+    let token = table.token.clone();
+    let seat = &table.seats[active];
+    let action_name = match action {
+        ActionCommand::Stand => "stand",
+        ActionCommand::Hit => "hit",
+        ActionCommand::DoubleDown { amount } => {
+            if amount != seat.bet_amount {
+                return Err(ContractError::WrongDoublDownAmount {
+                    amount: seat.bet_amount,
+                });
+            }
+
+            if seat.cards.len() != 2 {
+                return Err(ContractError::DoubleDownNotAllowed {});
+            }
+
+            let limits = whitelisted(deps.storage, &token)?.bet_limits;
+            check_bet_limits(deps.storage, &token, &limits, amount)?;
+
+            // covered by the reservation made at bet
+            let _ = exec_bet(deps.storage, &info, &token, amount)?;
+            reserve(deps.storage, &token, amount, Uint128::zero())?;
+            "doubledown"
+        }
+        _ => return Err(ContractError::ActionNotOffered {}),
+    };
+
+    table.pending = Some(house::request_seed(
+        deps.storage,
+        &env,
+        Move::Action(action),
+        &SeedRequest::Table { table_id },
+    )?);
+    table.turn_started = env.block.time;
+    TABLES.save(deps.storage, U64Key::new(table_id), &table)?;
+
+    Ok(Response::new()
+        .add_attribute("action", action_name)
+        .add_attribute("table_id", table_id.to_string()))
+}
+
+/// The seat to act stands once its turn timed out, so one idle player cannot hold up the table.
+/// A house that did not reveal the seed of the last move in time pays every seat
+/// all it reserved for it.
+pub fn try_force_stand(deps: DepsMut, env: Env, table_id: u64) -> Result<Response, ContractError> {
+    let mut table = load_table(&deps, table_id)?;
+    if table.phase != TablePhase::Playing {
+        return Err(ContractError::ActionBeforeBet {});
+    }
+
+    let seconds_left = turn_left(&table, &env);
+    if seconds_left > 0 {
+        return Err(ContractError::NotTimedOut { seconds_left });
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "force_stand")
+        .add_attribute("table_id", table_id.to_string());
+    let res = match table.pending.take() {
+        Some(pending) => {
+            house::cancel_request(deps.storage, &pending);
+            res.add_attributes(house_timeout(deps.storage, &env, table_id, &mut table)?)
+        }
+        None => {
+            table.pending = Some(house::request_seed(
+                deps.storage,
+                &env,
+                Move::ForceSettle(TimeoutAction::Stand),
+                &SeedRequest::Table { table_id },
+            )?);
+            table.turn_started = env.block.time;
+            res.add_attribute(
+                "player",
+                table.seats[table.active_seat as usize].player.clone(),
+            )
+        }
+    };
+    TABLES.save(deps.storage, U64Key::new(table_id), &table)?;
+
+    Ok(res)
+}
+
+/// Draw the move the table waits on from the house seed just revealed.
+pub(crate) fn draw_table(
+    storage: &mut dyn Storage,
+    env: &Env,
+    table_id: u64,
+    house_seed: &Binary,
+) -> Result<Response, ContractError> {
+    let mut table = TABLES.load(storage, U64Key::new(table_id))?;
+    let pending = table.pending.take().ok_or(ContractError::InvalidState {})?;
+    table.turn_started = env.block.time;
+
+    if let Some(mut entropy) = TABLE_ENTROPY.may_load(storage, U64Key::new(table_id))? {
+        entropy.house_seeds.push(HouseSeed {
+            made_for: pending.made_for.clone(),
+            index: pending.index,
+            seed: house_seed.clone(),
+            made_at: pending.made_at,
+        });
+        TABLE_ENTROPY.save(storage, U64Key::new(table_id), &entropy)?;
+    }
+
+    let config = CONFIG.load(storage)?;
+    let mut shoe = TABLE_SHOE.load(storage, U64Key::new(table_id))?;
+    let mut rng = random::gen_rng(&table_seed(&table), house_seed, pending.made_at);
+
+    let mut res = Response::new().add_attribute("table_id", table_id.to_string());
+    let next = match pending.made_for {
+        Move::Deal => {
+            let next = play::deal_table(&mut table, &mut shoe, &mut rng);
+            res = res.add_attribute("dealer_cards", hand_to_string(&table.dealer_hand));
+            next
+        }
+        Move::Action(action) => {
+            let active = table.active_seat as usize;
+            let next = play::act_seat(&mut table, &action, &mut shoe, &mut rng);
+            res = res.add_attribute("player_cards", hand_to_string(&table.seats[active].cards));
+            next
+        }
+        Move::ForceSettle(_) => play::next_seat(&mut table),
+    };
+    let res = match next {
+        Some(seat) => res
+            .add_attribute("state", "playing")
+            .add_attribute("seat", seat.to_string()),
+        None => res.add_attributes(settle_table(
+            storage, env, &config, table_id, &mut table, &mut shoe, &mut rng,
+        )?),
+    };
+
+    TABLES.save(storage, U64Key::new(table_id), &table)?;
+    TABLE_SHOE.save(storage, U64Key::new(table_id), &shoe)?;
+
+    Ok(res)
+}
+
+/// The dealer plays once for the whole table, then each seat is judged and paid on its own.
+/// Every seat's round goes to its player's history.
+fn settle_table<T: rand::Rng>(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    table_id: u64,
+    table: &mut Table,
+    shoe: &mut Shoe,
+    rng: &mut T,
+) -> Result<Vec<(&'static str, String)>, ContractError> {
+    play::dealer_play_table(table, &rules(config), shoe, rng);
+
+    let entropy = take_entropy(storage, table_id, table)?;
+    let mut results = vec![];
+    let mut round_ids = vec![];
+    for seat in table.seats.iter_mut().filter(|s| in_play(s)) {
+        let judge = game::judge(&table.dealer_hand, &seat.cards);
+        let payout = config
+            .payout_table
+            .payout(seat.bet_amount, GameResult::from(judge));
+        let round_id = close_seat(
+            storage,
+            env,
+            &table.token,
+            &table.dealer_hand,
+            seat,
+            judge,
+            payout,
+            entropy.as_ref(),
+        )?;
+        results.push(GameResult::from(judge).to_string());
+        round_ids.push(round_id.to_string());
+    }
+
+    Ok(end_round(table_id, table, results, round_ids))
+}
+
+/// Every seat in play is paid all the bankroll reserved for it, the hole card stays down.
+fn house_timeout(
+    storage: &mut dyn Storage,
+    env: &Env,
+    table_id: u64,
+    table: &mut Table,
+) -> Result<Vec<(&'static str, String)>, ContractError> {
+    let entropy = take_entropy(storage, table_id, table)?;
+    let mut results = vec![];
+    let mut round_ids = vec![];
+    for seat in table.seats.iter_mut().filter(|s| in_play(s)) {
+        let judge = Judge::HouseTimeout(game::calc_score(&seat.cards));
+        let payout = seat.liability;
+        let round_id = close_seat(
+            storage,
+            env,
+            &table.token,
+            &table.dealer_hand,
+            seat,
+            judge,
+            payout,
+            entropy.as_ref(),
+        )?;
+        results.push(GameResult::from(judge).to_string());
+        round_ids.push(round_id.to_string());
+    }
+
+    Ok(end_round(table_id, table, results, round_ids))
+}
+
+/// Table rules: no insurance, the dealer as configured.
+fn rules(config: &Config) -> Rules {
+    Rules {
+        insurance: false,
+        ..Rules::of(config)
+    }
+}
+
+/// The entropy of the round with the seeds the seats revealed, no longer in progress.
+/// Rounds dealt before entropy was kept have none.
+fn take_entropy(
+    storage: &mut dyn Storage,
+    table_id: u64,
+    table: &Table,
+) -> StdResult<Option<RoundEntropy>> {
+    let mut entropy = match TABLE_ENTROPY.may_load(storage, U64Key::new(table_id))? {
+        Some(entropy) => entropy,
+        None => return Ok(None),
+    };
+    for player in entropy.players.iter_mut() {
+        if let Some(seat) = table.seats.iter().find(|s| s.player == player.player) {
+            player.seed = seat.player_seed.clone();
+        }
+    }
+    TABLE_ENTROPY.remove(storage, U64Key::new(table_id));
+    Ok(Some(entropy))
+}
+
+/// Pay a seat, keep its round and the round's entropy in the player's history
+/// and clear the seat for the next round.
+#[allow(clippy::too_many_arguments)]
+fn close_seat(
+    storage: &mut dyn Storage,
+    env: &Env,
+    token: &AssetInfo,
+    dealer_hand: &Hand,
+    seat: &mut Seat,
+    judge: Judge,
+    payout: Uint128,
+    entropy: Option<&RoundEntropy>,
+) -> Result<u64, ContractError> {
+    let result = GameResult::from(judge);
+    release(storage, &seat.player, token, seat.liability, payout)?;
+
+    let round = Round {
+        round_id: next_round_id(storage)?,
+        token: token.clone(),
+        dealer_hand: dealer_hand.clone(),
+        player_hands: vec![PlayerHand {
+            cards: std::mem::take(&mut seat.cards),
+            bet_amount: seat.bet_amount,
+        }],
+        results: vec![HandResult { result, judge }],
+        insurance: None,
+        bet: seat.bet_amount,
+        side_bets: Uint128::zero(),
+        payout,
+        height: env.block.height,
+    };
+    record_round(storage, &seat.player, &round)?;
+    if let Some(entropy) = entropy {
+        ROUND_ENTROPY.save(
+            storage,
+            (&seat.player, U64Key::new(round.round_id)),
+            entropy,
+        )?;
+    }
+
+    seat.bet_amount = Uint128::zero();
+    seat.seed_commitment = Binary::default();
+    seat.player_seed = None;
+    seat.liability = Uint128::zero();
+    Ok(round.round_id)
+}
+
+/// Back to betting for the next round.
+fn end_round(
+    table_id: u64,
+    table: &mut Table,
+    results: Vec<String>,
+    round_ids: Vec<String>,
+) -> Vec<(&'static str, String)> {
+    table.phase = TablePhase::Betting;
+    table.active_seat = 0;
+
+    vec![
+        ("state", "end".to_string()),
+        ("table_id", table_id.to_string()),
+        ("dealer_cards", hand_to_string(&table.dealer_hand)),
+        ("result", results.join(", ")),
+        ("round_id", round_ids.join(", ")),
+    ]
+}
+
+/// Seconds until the betting closes, once a bet started the window.
+fn betting_left(table: &Table, env: &Env) -> Option<u64> {
+    table
+        .betting_ends
+        .map(|ends| ends.seconds().saturating_sub(env.block.time.seconds()))
+}
+
+/// Seconds until the seat to act can be made to stand, zero once it timed out.
+fn turn_left(table: &Table, env: &Env) -> u64 {
+    table
+        .turn_started
+        .plus_seconds(table.turn_timeout)
+        .seconds()
+        .saturating_sub(env.block.time.seconds())
+}
+
+fn table_response(env: &Env, table_id: u64, table: Table) -> TableResponse {
+    let time_left = match table.phase {
+        TablePhase::Betting => betting_left(&table, env),
+        TablePhase::Playing => Some(turn_left(&table, env)),
+    };
+    let dealer_score = game::calc_soft_score(&table.dealer_hand);
+    let seat_scores = table
+        .seats
+        .iter()
+        .map(|s| game::calc_soft_score(&s.cards))
+        .collect();
+
+    TableResponse {
+        table_id,
+        table,
+        dealer_score,
+        seat_scores,
+        time_left,
+    }
+}
+
+pub fn query_table(deps: Deps, env: Env, table_id: u64) -> StdResult<TableResponse> {
+    let table = TABLES.load(deps.storage, U64Key::new(table_id))?;
+
+    Ok(table_response(&env, table_id, table))
+}
+
+pub fn query_tables(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<TablesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+
+    let tables = TABLES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, table) = item?;
+            let mut id = [0u8; 8];
+            id.copy_from_slice(&key);
+            Ok(table_response(&env, u64::from_be_bytes(id), table))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TablesResponse { tables })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{ranks, Rank, CARDLIST};
+    use crate::contract::{query, DEFAULT_DECKS};
+    use crate::msg::{BankrollResponse, DepositResponse, ExecuteMsg, HistoryResponse, QueryMsg};
+    use crate::testing::{
+        self, execute, init, instantiate_msg, provide_liquidity, seed, seed_commitment, TestDeps,
+    };
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::{from_binary, Addr};
+
+    const PLAYERS: [&str; 2] = ["user0000", "user0001"];
+
+    /// One table in token0000, players with 1000 in the vault each.
+    fn init_table() -> TestDeps {
+        let mut deps = init(instantiate_msg());
+        for player in PLAYERS {
+            testing::deposit(&mut deps, player, 1000);
+        }
+        provide_liquidity(&mut deps, 1_000_000);
+
+        let msg = ExecuteMsg::OpenTable {
+            token: testing::token("token0000"),
+            betting_window: None,
+            turn_timeout: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!("1", res.attributes[1].value);
+
+        deps
+    }
+
+    /// A full shoe of sevens, so every card the table deals is known.
+    fn stack_sevens(deps: &mut TestDeps) {
+        let shoe = Shoe {
+            decks: DEFAULT_DECKS,
+            remaining: CARDLIST
+                .iter()
+                .map(|c| match c.rank {
+                    Rank::Seven => 13 * DEFAULT_DECKS as u32,
+                    _ => 0,
+                })
+                .collect(),
+            in_play: vec![],
+        };
+        TABLE_SHOE
+            .save(&mut deps.storage, U64Key::new(1), &shoe)
+            .unwrap();
+    }
+
+    fn get_table(deps: &TestDeps, env: &Env) -> TableResponse {
+        let msg = QueryMsg::GetTable { table_id: 1 };
+        from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap()
+    }
+
+    fn bet(deps: &mut TestDeps, env: Env, player: &str) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::TableBet {
+            table_id: 1,
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment(player),
+        };
+        execute(deps.as_mut(), env, mock_info(player, &[]), msg)
+    }
+
+    fn deposit(deps: &TestDeps, player: &str) -> Uint128 {
+        let msg = QueryMsg::GetDeposit {
+            address: player.to_string(),
+            asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+        };
+        let res: DepositResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.deposit
+    }
+
+    #[test]
+    fn seats() {
+        let mut deps = init_table();
+
+        let msg = ExecuteMsg::OpenTable {
+            token: testing::token("token0001"),
+            betting_window: None,
+            turn_timeout: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg);
+        assert_eq!(
+            ContractError::TokenNotWhitelisted {
+                token: "token0001".to_string()
+            },
+            res.unwrap_err()
+        );
+        let msg = ExecuteMsg::OpenTable {
+            token: testing::token("token0000"),
+            betting_window: None,
+            turn_timeout: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg);
+        assert_eq!(ContractError::Unauthorized {}, res.unwrap_err());
+
+        for i in 0..MAX_SEATS {
+            let msg = ExecuteMsg::JoinTable { table_id: 1 };
+            let player = format!("user{:04}", i);
+            execute(deps.as_mut(), mock_env(), mock_info(&player, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::JoinTable { table_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg);
+        assert_eq!(ContractError::AlreadySeated {}, res.unwrap_err());
+        let msg = ExecuteMsg::JoinTable { table_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0100", &[]), msg);
+        assert_eq!(ContractError::TableFull {}, res.unwrap_err());
+
+        // a seat with a bet stays until the round is over
+        bet(&mut deps, mock_env(), "user0000").unwrap();
+        let msg = ExecuteMsg::LeaveTable { table_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg);
+        assert_eq!(ContractError::AlreadyBet {}, res.unwrap_err());
+        let res = bet(&mut deps, mock_env(), "user0000");
+        assert_eq!(ContractError::AlreadyBet {}, res.unwrap_err());
+
+        let msg = ExecuteMsg::LeaveTable { table_id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("user0001", &[]), msg).unwrap();
+        let res = bet(&mut deps, mock_env(), "user0001");
+        assert_eq!(ContractError::NotSeated {}, res.unwrap_err());
+
+        let res = get_table(&deps, &mock_env());
+        assert_eq!(MAX_SEATS - 1, res.table.seats.len());
+        assert_eq!(Some(DEFAULT_BETTING_WINDOW), res.time_left);
+
+        let msg = QueryMsg::GetTables {
+            start_after: None,
+            limit: None,
+        };
+        let res: TablesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(1, res.tables.len());
+        assert_eq!(1, res.tables[0].table_id);
+    }
+
+    #[test]
+    fn round() {
+        let mut deps = init_table();
+        for player in PLAYERS {
+            let msg = ExecuteMsg::JoinTable { table_id: 1 };
+            execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg).unwrap();
+        }
+
+        // the window stays open until every seat has bet
+        bet(&mut deps, mock_env(), PLAYERS[0]).unwrap();
+        let msg = ExecuteMsg::DealTable { table_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        assert_eq!(
+            ContractError::BettingOpen {
+                seconds_left: DEFAULT_BETTING_WINDOW
+            },
+            res.unwrap_err()
+        );
+        bet(&mut deps, mock_env(), PLAYERS[1]).unwrap();
+        stack_sevens(&mut deps);
+        let msg = ExecuteMsg::DealTable { table_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!("deal_table", res.attributes[0].value);
+
+        // two sevens to each seat and one up for the dealer, who does not peek
+        let table = get_table(&deps, &mock_env());
+        assert_eq!(TablePhase::Playing, table.table.phase);
+        assert_eq!(0, table.table.active_seat);
+        assert_eq!(None, table.table.dealer_blackjack);
+        assert_eq!(vec![Rank::Seven], ranks(&table.table.dealer_hand));
+        for seat in table.table.seats.iter() {
+            assert_eq!(vec![Rank::Seven, Rank::Seven], ranks(&seat.cards));
+        }
+
+        let msg = ExecuteMsg::TableAction {
+            table_id: 1,
+            action: ActionCommand::Stand,
+            seed: Some(seed(PLAYERS[1])),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(PLAYERS[1], &[]), msg);
+        assert_eq!(ContractError::NotYourTurn {}, res.unwrap_err());
+        let msg = ExecuteMsg::TableAction {
+            table_id: 1,
+            action: ActionCommand::Split {
+                amount: Uint128::new(100),
+            },
+            seed: Some(seed(PLAYERS[0])),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(PLAYERS[0], &[]), msg);
+        assert_eq!(ContractError::ActionNotOffered {}, res.unwrap_err());
+
+        // the first seat stands, the turn passes to the second
+        let msg = ExecuteMsg::TableAction {
+            table_id: 1,
+            action: ActionCommand::Stand,
+            seed: Some(seed(PLAYERS[0])),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(PLAYERS[0], &[]), msg).unwrap();
+        let table = get_table(&deps, &mock_env());
+        assert_eq!(TablePhase::Playing, table.table.phase);
+        assert_eq!(1, table.table.active_seat);
+        let msg = ExecuteMsg::TableAction {
+            table_id: 1,
+            action: ActionCommand::Stand,
+            seed: Some(seed(PLAYERS[0])),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(PLAYERS[0], &[]), msg);
+        assert_eq!(ContractError::NotYourTurn {}, res.unwrap_err());
+
+        // the second seat hits to 21, which ends its turn and the dealer plays
+        let msg = ExecuteMsg::TableAction {
+            table_id: 1,
+            action: ActionCommand::Hit,
+            seed: Some(seed(PLAYERS[1])),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(PLAYERS[1], &[]), msg).unwrap();
+        let table = get_table(&deps, &mock_env());
+        assert_eq!(TablePhase::Betting, table.table.phase);
+        assert_eq!(
+            vec![Rank::Seven, Rank::Seven, Rank::Seven],
+            ranks(&table.table.dealer_hand)
+        );
+
+        // each seat is settled on its own against the same dealer hand
+        assert!(table.table.seats.iter().all(|s| s.bet_amount.is_zero()));
+        for (player, payout) in PLAYERS.iter().zip([0, 100]) {
+            let msg = QueryMsg::GetHistory {
+                address: player.to_string(),
+                start_after: None,
+                limit: None,
+            };
+            let res: HistoryResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(1, res.rounds.len());
+            let round = &res.rounds[0];
+            assert_eq!(table.table.dealer_hand, round.dealer_hand);
+            assert_eq!(Uint128::new(100), round.bet);
+            assert_eq!(Uint128::new(payout), round.payout);
+            assert_eq!(Uint128::new(900 + payout), deposit(&deps, player));
+        }
+
+        let msg = QueryMsg::GetBankroll {
+            token: testing::token("token0000"),
+        };
+        let res: BankrollResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(Uint128::zero(), res.liabilities);
+        assert_eq!(Uint128::new(1_000_100), res.balance);
+
+        // the seats stay for the next round
+        bet(&mut deps, mock_env(), PLAYERS[0]).unwrap();
+    }
+
+    #[test]
+    fn timeouts() {
+        let mut deps = init_table();
+        for player in PLAYERS {
+            let msg = ExecuteMsg::JoinTable { table_id: 1 };
+            execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::DealTable { table_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        assert_eq!(ContractError::ActionBeforeBet {}, res.unwrap_err());
+
+        // the second seat missed the betting window and sits the round out
+        bet(&mut deps, mock_env(), PLAYERS[0]).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(DEFAULT_BETTING_WINDOW);
+        let res = bet(&mut deps, env.clone(), PLAYERS[1]);
+        assert_eq!(ContractError::BettingClosed {}, res.unwrap_err());
+        stack_sevens(&mut deps);
+        let msg = ExecuteMsg::DealTable { table_id: 1 };
+        execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+
+        let table = get_table(&deps, &env);
+        assert!(table.table.seats[1].cards.is_empty());
+        assert_eq!(TablePhase::Playing, table.table.phase);
+        assert_eq!(0, table.table.active_seat);
+        assert_eq!(Some(DEFAULT_TURN_TIMEOUT), table.time_left);
+        let msg = ExecuteMsg::ForceStand { table_id: 1 };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg);
+        assert_eq!(
+            ContractError::NotTimedOut {
+                seconds_left: DEFAULT_TURN_TIMEOUT
+            },
+            res.unwrap_err()
+        );
+
+        // the seat stands on 14 and loses to the dealer's 21
+        env.block.time = env.block.time.plus_seconds(DEFAULT_TURN_TIMEOUT);
+        let msg = ExecuteMsg::ForceStand { table_id: 1 };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!("force_stand", res.attributes[0].value);
+
+        let table = get_table(&deps, &env);
+        assert_eq!(TablePhase::Betting, table.table.phase);
+        assert_eq!(None, table.time_left);
+        assert_eq!(
+            vec![Rank::Seven, Rank::Seven, Rank::Seven],
+            ranks(&table.table.dealer_hand)
+        );
+        assert_eq!(Uint128::new(900), deposit(&deps, PLAYERS[0]));
+        assert_eq!(Uint128::new(1000), deposit(&deps, PLAYERS[1]));
+    }
+}
@@ -0,0 +1,138 @@
+//! Fixtures shared by the unit tests.
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    to_binary, Addr, Binary, DepsMut, Env, MessageInfo, Order, OwnedDeps, Response, Uint128,
+};
+use cw20::Cw20ReceiveMsg;
+use cw_storage_plus::U64Key;
+
+use crate::contract::{self, instantiate};
+use crate::error::ContractError;
+use crate::msg::{Cw20HookMsg, ExecuteMsg, InstantiateMsg};
+use crate::random;
+use crate::state::{AssetInfo, HOUSE_CHAIN, SEED_REQUESTS};
+
+pub type TestDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+/// Default config with token0000 whitelisted.
+pub fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        cw20_address: "token0000".to_string(),
+        insurance: None,
+        payout_table: None,
+        decks: None,
+        penetration: None,
+        dealer_hits_soft_17: None,
+        perfect_pairs: None,
+        twenty_one_plus_three: None,
+        bet_limits: None,
+        timeout: None,
+        timeout_action: None,
+    }
+}
+
+/// Seeds in the house chain of the tests
+pub const CHAIN_LENGTH: u64 = 1000;
+
+/// Seed `index` of the house chain, the anchor at 0 and each one hashing to the one before.
+pub fn house_seed(index: u64) -> Binary {
+    let mut seed = random::sha256(b"house secret seed");
+    for _ in index..CHAIN_LENGTH {
+        seed = random::sha256(&seed);
+    }
+    Binary(seed)
+}
+
+/// Instantiated by "creator", who commits to the house chain.
+pub fn init(msg: InstantiateMsg) -> TestDeps {
+    let mut deps = mock_dependencies(&[]);
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    assert_eq!(0, res.messages.len());
+    let msg = ExecuteMsg::CommitHouseChain {
+        anchor: house_seed(0),
+    };
+    contract::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    deps
+}
+
+/// Runs `msg` like [`contract::execute`], then reveals every house seed a move waits on
+/// so its cards are drawn as if the house answered in the same block.
+/// The attributes and messages of the reveals follow those of `msg`.
+pub fn execute(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let mut res = contract::execute(deps.branch(), env.clone(), info, msg)?;
+    for drawn in reveal_house_seeds(deps, env) {
+        // without the action and index of the reveal
+        res.attributes.extend(drawn.attributes.into_iter().skip(2));
+        res.messages.extend(drawn.messages);
+    }
+    Ok(res)
+}
+
+/// Reveal the house seed the moves made since the last one wait on, if any do.
+pub fn reveal_house_seeds(deps: DepsMut, env: Env) -> Vec<Response> {
+    let chain = match HOUSE_CHAIN.may_load(deps.storage).unwrap() {
+        Some(chain) => chain,
+        None => return vec![],
+    };
+    let waiting = SEED_REQUESTS
+        .prefix(U64Key::new(chain.next))
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next();
+    if waiting.is_none() {
+        return vec![];
+    }
+    let msg = ExecuteMsg::RevealHouseSeed {
+        index: chain.next,
+        seed: house_seed(chain.next - chain.anchor),
+    };
+    let info = mock_info("anyone", &[]);
+    vec![contract::execute(deps, env, info, msg).unwrap()]
+}
+
+/// 1000 in user0000's vault and a bankroll of 1,000,000, all in token0000.
+pub fn init_with_balance() -> TestDeps {
+    let mut deps = init(instantiate_msg());
+    deposit(&mut deps, "user0000", 1000);
+    provide_liquidity(&mut deps, 1_000_000);
+    deps
+}
+
+/// A cw20 transfer of `amount` from `sender` with `msg` attached.
+pub fn hook(sender: &str, amount: u128, msg: &Cw20HookMsg) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: sender.to_string(),
+        amount: Uint128::new(amount),
+        msg: to_binary(msg).unwrap(),
+    })
+}
+
+pub fn deposit(deps: &mut TestDeps, player: &str, amount: u128) {
+    let msg = hook(player, amount, &Cw20HookMsg::Deposit {});
+    contract::execute(deps.as_mut(), mock_env(), mock_info("token0000", &[]), msg).unwrap();
+}
+
+/// Liquidity from "creator".
+pub fn provide_liquidity(deps: &mut TestDeps, amount: u128) {
+    let msg = hook("creator", amount, &Cw20HookMsg::ProvideLiquidity {});
+    contract::execute(deps.as_mut(), mock_env(), mock_info("token0000", &[]), msg).unwrap();
+}
+
+/// The cw20 at `address`.
+pub fn token(address: &str) -> AssetInfo {
+    AssetInfo::cw20(&Addr::unchecked(address))
+}
+
+pub fn seed(player: &str) -> Binary {
+    Binary::from(format!("{} secret seed", player).as_bytes())
+}
+
+pub fn seed_commitment(player: &str) -> Binary {
+    Binary(random::sha256(&seed(player)))
+}
@@ -0,0 +1,822 @@
+use cosmwasm_std::{
+    Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult,
+    Storage, Timestamp, Uint128,
+};
+use cw_storage_plus::U64Key;
+
+use crate::card::hand_to_string;
+use crate::contract::{credit, exec_bet, only_owner, reveal_seed, settle, whitelisted, Settlement};
+use crate::error::ContractError;
+use crate::game;
+use crate::msg::{
+    ActionCommand, Standing, StandingsResponse, TournamentEntryResponse, TournamentResponse,
+};
+use crate::play::{self, Rules, Turn};
+use crate::shoe::Shoe;
+use crate::state::{
+    AssetInfo, Config, Entry, GameState, Move, PlayerHand, SeedRequest, Tournament, CONFIG,
+    ENTRIES, STATE, TOURNAMENTS, TOURNAMENT_COUNT, TOURNAMENT_SHOE,
+};
+use crate::{house, random};
+
+/// Bounds the work of settling a tournament
+pub const MAX_ENTRIES: u32 = 100;
+
+fn load_tournament(storage: &dyn Storage, id: u64) -> Result<Tournament, ContractError> {
+    Ok(TOURNAMENTS.load(storage, U64Key::new(id))?)
+}
+
+fn load_entry(storage: &dyn Storage, id: u64, address: &Addr) -> Result<Entry, ContractError> {
+    ENTRIES
+        .may_load(storage, (U64Key::new(id), address))?
+        .ok_or(ContractError::NotRegistered {})
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn try_create_tournament(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token: AssetInfo,
+    buy_in: Uint128,
+    starting_chips: Uint128,
+    starts_at: Timestamp,
+    ends_at: Option<Timestamp>,
+    rounds: Option<u32>,
+    payouts: Vec<Decimal>,
+) -> Result<Response, ContractError> {
+    only_owner(deps.storage, &info)?;
+
+    whitelisted(deps.storage, &token)?;
+
+    let total: Decimal = payouts.iter().fold(Decimal::zero(), |sum, p| sum + *p);
+    if buy_in.is_zero()
+        || starting_chips.is_zero()
+        || starts_at < env.block.time
+        || (ends_at.is_none() && rounds.is_none())
+        || matches!(ends_at, Some(ends) if ends <= starts_at)
+        || rounds == Some(0)
+        || payouts.len() > MAX_ENTRIES as usize
+        || payouts.iter().any(|p| p.is_zero())
+        || total != Decimal::one()
+    {
+        return Err(ContractError::InvalidTournament {});
+    }
+
+    let tournament_id = TOURNAMENT_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    TOURNAMENT_COUNT.save(deps.storage, &tournament_id)?;
+    let tournament = Tournament {
+        token: token.clone(),
+        buy_in,
+        starting_chips,
+        starts_at,
+        ends_at,
+        rounds,
+        payouts,
+        prize_pool: Uint128::zero(),
+        entries: 0,
+        settled: false,
+    };
+    TOURNAMENTS.save(deps.storage, U64Key::new(tournament_id), &tournament)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "create_tournament")
+        .add_attribute("tournament_id", tournament_id.to_string())
+        .add_attribute("token", token.to_string()))
+}
+
+/// The buy-in goes to the prize pool for a stack of starting chips.
+pub fn try_register(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tournament_id: u64,
+) -> Result<Response, ContractError> {
+    let mut tournament = load_tournament(deps.storage, tournament_id)?;
+    if env.block.time >= tournament.starts_at {
+        return Err(ContractError::RegistrationClosed {});
+    }
+    let key = (U64Key::new(tournament_id), &info.sender);
+    if ENTRIES.has(deps.storage, key.clone()) {
+        return Err(ContractError::AlreadyRegistered {});
+    }
+    if tournament.entries >= MAX_ENTRIES {
+        return Err(ContractError::TournamentFull {});
+    }
+
+    let balance_after = exec_bet(deps.storage, &info, &tournament.token, tournament.buy_in)?;
+    let entry = Entry {
+        number: tournament.entries,
+        chips: tournament.starting_chips,
+        rounds: 0,
+        game: None,
+        prize: Uint128::zero(),
+    };
+    ENTRIES.save(deps.storage, key, &entry)?;
+    tournament.entries += 1;
+    tournament.prize_pool += tournament.buy_in;
+    TOURNAMENTS.save(deps.storage, U64Key::new(tournament_id), &tournament)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "register_tournament")
+        .add_attribute("tournament_id", tournament_id.to_string())
+        .add_attribute("chips", entry.chips)
+        .add_attribute("balance_after", balance_after.balance))
+}
+
+fn ended(tournament: &Tournament, env: &Env) -> bool {
+    tournament.settled || matches!(tournament.ends_at, Some(ends) if env.block.time >= ends)
+}
+
+fn in_game(entry: &Entry) -> bool {
+    entry.game.as_ref().map(|g| g.ingame).unwrap_or_default()
+}
+
+/// Out of chips or of rounds, with no round left to settle.
+fn finished(tournament: &Tournament, entry: &Entry) -> bool {
+    !in_game(entry)
+        && (entry.chips.is_zero() || matches!(tournament.rounds, Some(r) if entry.rounds >= r))
+}
+
+/// No move for longer than the turn timeout, counted from the start if it never played.
+/// Settling does not wait on idle players.
+fn idle(tournament: &Tournament, entry: &Entry, config: &Config, env: &Env) -> bool {
+    let last_move = entry
+        .game
+        .as_ref()
+        .map(|g| g.turn_started)
+        .unwrap_or(tournament.starts_at);
+    env.block.time >= last_move.plus_seconds(config.timeout)
+}
+
+/// Rules of the game without insurance, which tournaments do not offer.
+fn rules(config: &Config) -> Rules {
+    Rules {
+        insurance: false,
+        ..Rules::of(config)
+    }
+}
+
+/// The payout of a settled round goes back to the chip stack.
+fn finish_round(entry: &mut Entry, settlement: &Settlement) {
+    entry.chips += settlement.payout;
+    entry.rounds += 1;
+}
+
+/// Deals a round with the same rules as [`crate::contract::try_bet`],
+/// without side bets. Chips at stake when the tournament ends are lost.
+/// Hands are settled in chips and not kept as rounds, so their entropy is not kept either.
+pub fn try_tournament_bet(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tournament_id: u64,
+    amount: Uint128,
+    seed_commitment: Binary,
+) -> Result<Response, ContractError> {
+    if STATE.load(deps.storage)?.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    let tournament = load_tournament(deps.storage, tournament_id)?;
+    if env.block.time < tournament.starts_at {
+        return Err(ContractError::TournamentNotStarted {});
+    }
+    let mut entry = load_entry(deps.storage, tournament_id, &info.sender)?;
+    if in_game(&entry) {
+        return Err(ContractError::BetAfterStart {});
+    }
+    if ended(&tournament, &env) || finished(&tournament, &entry) {
+        return Err(ContractError::TournamentOver {});
+    }
+    if amount.is_zero() {
+        return Err(ContractError::BetAmountZero {});
+    }
+    if amount > entry.chips {
+        return Err(ContractError::InsufficientChips { chips: entry.chips });
+    }
+    if seed_commitment.len() != random::COMMITMENT_LENGTH {
+        return Err(ContractError::InvalidCommitment {});
+    }
+    entry.chips -= amount;
+
+    let config = CONFIG.load(deps.storage)?;
+    let key = (U64Key::new(tournament_id), &info.sender);
+    let mut shoe = match TOURNAMENT_SHOE.may_load(deps.storage, key.clone())? {
+        Some(shoe) if shoe.decks == config.decks && !shoe.needs_shuffle(config.penetration) => shoe,
+        _ => Shoe::new(config.decks),
+    };
+    shoe.discard();
+
+    let request = SeedRequest::Tournament {
+        tournament_id,
+        player: info.sender.clone(),
+    };
+    entry.game = Some(GameState {
+        ingame: true,
+        total_bet_amount: amount,
+        player_hands: vec![PlayerHand {
+            cards: vec![],
+            bet_amount: amount,
+        }],
+        seed_commitment,
+        pending: Some(house::request_seed(
+            deps.storage,
+            &env,
+            Move::Deal,
+            &request,
+        )?),
+        started_height: env.block.height,
+        started_at: env.block.time,
+        turn_started: env.block.time,
+        ..GameState::default()
+    });
+    ENTRIES.save(deps.storage, key.clone(), &entry)?;
+    TOURNAMENT_SHOE.save(deps.storage, key, &shoe)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "tournament_bet")
+        .add_attribute("tournament_id", tournament_id.to_string())
+        .add_attribute("bet_amount", amount)
+        .add_attribute("chips", entry.chips))
+}
+
+/// Stand, hit, double down from the chips or surrender, as in [`crate::contract::try_action`].
+pub fn try_tournament_action(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tournament_id: u64,
+    action: ActionCommand,
+    seed: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let tournament = load_tournament(deps.storage, tournament_id)?;
+    let mut entry = load_entry(deps.storage, tournament_id, &info.sender)?;
+    let mut game = match entry.game.take() {
+        Some(game) if game.ingame => game,
+        _ => return Err(ContractError::ActionBeforeBet {}),
+    };
+    if ended(&tournament, &env) {
+        return Err(ContractError::TournamentOver {});
+    }
+    if game.pending.is_some() {
+        return Err(ContractError::SeedPending {});
+    }
+
+    let player_seed = reveal_seed(game.player_seed.clone(), seed, &game.seed_commitment)?;
+    game.player_seed = Some(player_seed);
+
+    let hand = &game.player_hands[0];
+    let action_name = match action {
+        ActionCommand::Stand => "stand",
+        ActionCommand::Hit => "hit",
+        ActionCommand::DoubleDown { amount } => {
+            if amount != hand.bet_amount {
+                return Err(ContractError::WrongDoublDownAmount {
+                    amount: hand.bet_amount,
+                });
+            }
+            if hand.cards.len() != 2 {
+                return Err(ContractError::DoubleDownNotAllowed {});
+            }
+            if amount > entry.chips {
+                return Err(ContractError::InsufficientChips { chips: entry.chips });
+            }
+
+            entry.chips -= amount;
+            "doubledown"
+        }
+        ActionCommand::Surrender => {
+            if hand.cards.len() != 2 {
+                return Err(ContractError::SurrenderNotAllowed {});
+            }
+            "surrender"
+        }
+        _ => return Err(ContractError::ActionNotOffered {}),
+    };
+
+    let request = SeedRequest::Tournament {
+        tournament_id,
+        player: info.sender.clone(),
+    };
+    game.pending = Some(house::request_seed(
+        deps.storage,
+        &env,
+        Move::Action(action),
+        &request,
+    )?);
+    game.turn_started = env.block.time;
+    entry.game = Some(game);
+    ENTRIES.save(
+        deps.storage,
+        (U64Key::new(tournament_id), &info.sender),
+        &entry,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", action_name)
+        .add_attribute("tournament_id", tournament_id.to_string()))
+}
+
+/// Draw the deal or action the entry waits on from the house seed just revealed.
+pub(crate) fn draw_tournament(
+    storage: &mut dyn Storage,
+    env: &Env,
+    tournament_id: u64,
+    player: &Addr,
+    house_seed: &Binary,
+) -> Result<Response, ContractError> {
+    let key = (U64Key::new(tournament_id), player);
+    let mut entry = ENTRIES.load(storage, key.clone())?;
+    let mut game = entry.game.take().ok_or(ContractError::InvalidState {})?;
+    let pending = game.pending.take().ok_or(ContractError::InvalidState {})?;
+    game.turn_started = env.block.time;
+
+    let config = CONFIG.load(storage)?;
+    let mut shoe = TOURNAMENT_SHOE.load(storage, key.clone())?;
+    // the commitment stands in until the seed is revealed with an action
+    let player_seed = game.player_seed.as_ref().unwrap_or(&game.seed_commitment);
+    let mut rng = random::gen_rng(player_seed, house_seed, pending.made_at);
+
+    let res = Response::new().add_attribute("tournament_id", tournament_id.to_string());
+    let turn = match pending.made_for {
+        Move::Action(action) => play::act(&mut game, &action, &mut shoe, &mut rng),
+        _ => play::deal(&mut game, &rules(&config), &mut shoe, &mut rng),
+    };
+
+    // a hit keeps the hand open until it busts
+    let res = if let Turn::Over(closing) = turn {
+        let settlement = settle(&mut game, &config, &mut shoe, &mut rng, closing);
+        finish_round(&mut entry, &settlement);
+        res.add_attribute("dealer_cards", hand_to_string(&game.dealer_hand))
+            .add_attribute("player_cards", hand_to_string(&game.player_hands[0].cards))
+            .add_attribute("state", "end")
+            .add_attributes(settlement.attributes())
+            .add_attribute("chips", entry.chips)
+    } else {
+        res.add_attribute("dealer_cards", hand_to_string(&game.dealer_hand))
+            .add_attribute("player_cards", hand_to_string(&game.player_hands[0].cards))
+    };
+
+    entry.game = Some(game);
+    ENTRIES.save(storage, key.clone(), &entry)?;
+    TOURNAMENT_SHOE.save(storage, key, &shoe)?;
+
+    Ok(res)
+}
+
+/// Registered players by chips, ties to the earliest registration.
+fn standings(storage: &dyn Storage, tournament_id: u64) -> StdResult<Vec<(Addr, Entry)>> {
+    let mut entries = ENTRIES
+        .prefix(U64Key::new(tournament_id))
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, entry) = item?;
+            let address =
+                String::from_utf8(key).map_err(|_| StdError::invalid_utf8("entries key"))?;
+            Ok((Addr::unchecked(address), entry))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    entries.sort_by(|(_, a), (_, b)| b.chips.cmp(&a.chips).then(a.number.cmp(&b.number)));
+    Ok(entries)
+}
+
+/// Once the end time passed or every player is done or idle, the prize pool is paid by place
+/// into the vaults. Shares of places without a player go to the first place.
+/// Rounds still open are closed and the chips at stake in them are lost.
+pub fn try_settle_tournament(
+    deps: DepsMut,
+    env: Env,
+    tournament_id: u64,
+) -> Result<Response, ContractError> {
+    let mut tournament = load_tournament(deps.storage, tournament_id)?;
+    if tournament.settled {
+        return Err(ContractError::TournamentOver {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let standings = standings(deps.storage, tournament_id)?;
+    let all_finished = env.block.time >= tournament.starts_at
+        && standings
+            .iter()
+            .all(|(_, e)| finished(&tournament, e) || idle(&tournament, e, &config, &env));
+    if !ended(&tournament, &env) && !all_finished {
+        return Err(ContractError::TournamentNotOver {});
+    }
+
+    let mut prizes: Vec<Uint128> = tournament
+        .payouts
+        .iter()
+        .take(standings.len())
+        .map(|share| tournament.prize_pool * *share)
+        .collect();
+    let paid: Uint128 = prizes.iter().copied().sum();
+    if let Some(first) = prizes.first_mut() {
+        *first += tournament.prize_pool - paid;
+    }
+
+    let mut winners = vec![];
+    for ((address, mut entry), prize) in standings.into_iter().zip(prizes) {
+        credit(deps.storage, &address, &tournament.token, prize)?;
+        if let Some(game) = entry.game.as_mut().filter(|g| g.ingame) {
+            if let Some(pending) = game.pending.take() {
+                house::cancel_request(deps.storage, &pending);
+            }
+            game.ingame = false;
+        }
+        entry.prize = prize;
+        ENTRIES.save(deps.storage, (U64Key::new(tournament_id), &address), &entry)?;
+        winners.push(format!("{} {}", address, prize));
+    }
+    tournament.settled = true;
+    TOURNAMENTS.save(deps.storage, U64Key::new(tournament_id), &tournament)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "settle_tournament")
+        .add_attribute("tournament_id", tournament_id.to_string())
+        .add_attribute("prize_pool", tournament.prize_pool)
+        .add_attribute("winners", winners.join(", ")))
+}
+
+pub fn query_tournament(deps: Deps, tournament_id: u64) -> StdResult<TournamentResponse> {
+    let tournament = TOURNAMENTS.load(deps.storage, U64Key::new(tournament_id))?;
+
+    Ok(TournamentResponse {
+        tournament_id,
+        tournament,
+    })
+}
+
+pub fn query_entry(
+    deps: Deps,
+    tournament_id: u64,
+    address: String,
+) -> StdResult<TournamentEntryResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let entry = ENTRIES.load(deps.storage, (U64Key::new(tournament_id), &address))?;
+    let mut player_scores = vec![];
+    let mut dealer_score = None;
+    if let Some(game) = entry.game.as_ref() {
+        player_scores = game
+            .player_hands
+            .iter()
+            .map(|h| game::calc_soft_score(&h.cards))
+            .collect();
+        dealer_score = Some(game::calc_soft_score(&game.dealer_hand));
+    }
+
+    Ok(TournamentEntryResponse {
+        address: address.to_string(),
+        entry,
+        player_scores,
+        dealer_score,
+    })
+}
+
+pub fn query_standings(deps: Deps, tournament_id: u64) -> StdResult<StandingsResponse> {
+    let standings = standings(deps.storage, tournament_id)?
+        .into_iter()
+        .map(|(address, entry)| Standing {
+            address: address.to_string(),
+            chips: entry.chips,
+            rounds: entry.rounds,
+            eliminated: entry.chips.is_zero() && !in_game(&entry),
+            prize: entry.prize,
+        })
+        .collect();
+
+    Ok(StandingsResponse {
+        tournament_id,
+        standings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::{query, DEFAULT_TIMEOUT};
+    use crate::msg::{DepositResponse, ExecuteMsg, QueryMsg};
+    use crate::testing::{self, execute, instantiate_msg, seed, seed_commitment, TestDeps};
+    use cosmwasm_std::from_binary;
+    use cosmwasm_std::testing::{mock_env, mock_info};
+
+    const PLAYERS: [&str; 2] = ["user0000", "user0001"];
+
+    fn starts_at() -> Timestamp {
+        mock_env().block.time.plus_seconds(100)
+    }
+
+    fn create_msg(ends_at: Option<Timestamp>, rounds: Option<u32>) -> ExecuteMsg {
+        ExecuteMsg::CreateTournament {
+            token: testing::token("token0000"),
+            buy_in: Uint128::new(100),
+            starting_chips: Uint128::new(1000),
+            starts_at: starts_at(),
+            ends_at,
+            rounds,
+            payouts: vec![Decimal::percent(70), Decimal::percent(30)],
+        }
+    }
+
+    /// Players with 1000 in the vault each and no bankroll, which tournaments do not use.
+    fn init() -> TestDeps {
+        let mut deps = testing::init(instantiate_msg());
+        for player in PLAYERS {
+            testing::deposit(&mut deps, player, 1000);
+        }
+
+        deps
+    }
+
+    fn register(deps: &mut TestDeps, env: Env) {
+        for player in PLAYERS {
+            let msg = ExecuteMsg::RegisterTournament { tournament_id: 1 };
+            execute(deps.as_mut(), env.clone(), mock_info(player, &[]), msg).unwrap();
+        }
+    }
+
+    /// Bets and stands unless the dealer blackjack ended the round.
+    fn play(deps: &mut TestDeps, env: Env, player: &str, amount: u128) {
+        let msg = ExecuteMsg::TournamentBet {
+            tournament_id: 1,
+            amount: Uint128::new(amount),
+            seed_commitment: seed_commitment(player),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(player, &[]), msg).unwrap();
+        if !res.attributes.iter().any(|a| a.key == "state") {
+            let msg = ExecuteMsg::TournamentAction {
+                tournament_id: 1,
+                action: ActionCommand::Stand,
+                seed: Some(seed(player)),
+            };
+            execute(deps.as_mut(), env, mock_info(player, &[]), msg).unwrap();
+        }
+    }
+
+    fn deposit(deps: &TestDeps, player: &str) -> Uint128 {
+        let msg = QueryMsg::GetDeposit {
+            address: player.to_string(),
+            asset: AssetInfo::cw20(&Addr::unchecked("token0000")),
+        };
+        let res: DepositResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.deposit
+    }
+
+    fn get_standings(deps: &TestDeps) -> Vec<Standing> {
+        let msg = QueryMsg::GetStandings { tournament_id: 1 };
+        let res: StandingsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.standings
+    }
+
+    #[test]
+    fn create() {
+        let mut deps = init();
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user0000", &[]),
+            create_msg(None, Some(2)),
+        );
+        assert_eq!(ContractError::Unauthorized {}, res.unwrap_err());
+
+        // needs an end and payouts adding up to the pool
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            create_msg(None, None),
+        );
+        assert_eq!(ContractError::InvalidTournament {}, res.unwrap_err());
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            create_msg(Some(starts_at()), None),
+        );
+        assert_eq!(ContractError::InvalidTournament {}, res.unwrap_err());
+        let msg = ExecuteMsg::CreateTournament {
+            token: testing::token("token0000"),
+            buy_in: Uint128::new(100),
+            starting_chips: Uint128::new(1000),
+            starts_at: starts_at(),
+            ends_at: None,
+            rounds: Some(2),
+            payouts: vec![Decimal::percent(70), Decimal::percent(20)],
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg);
+        assert_eq!(ContractError::InvalidTournament {}, res.unwrap_err());
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            create_msg(None, Some(2)),
+        )
+        .unwrap();
+        assert_eq!("1", res.attributes[1].value);
+
+        register(&mut deps, mock_env());
+        let msg = ExecuteMsg::RegisterTournament { tournament_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg);
+        assert_eq!(ContractError::AlreadyRegistered {}, res.unwrap_err());
+        let msg = ExecuteMsg::RegisterTournament { tournament_id: 1 };
+        let mut env = mock_env();
+        env.block.time = starts_at();
+        let res = execute(deps.as_mut(), env, mock_info("user0002", &[]), msg);
+        assert_eq!(ContractError::RegistrationClosed {}, res.unwrap_err());
+
+        // the buy-ins make the prize pool, the chips are apart from the vault
+        let msg = QueryMsg::GetTournament { tournament_id: 1 };
+        let res: TournamentResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(Uint128::new(200), res.tournament.prize_pool);
+        assert_eq!(2, res.tournament.entries);
+        assert_eq!(Uint128::new(900), deposit(&deps, "user0000"));
+        let msg = QueryMsg::GetTournamentEntry {
+            tournament_id: 1,
+            address: "user0000".to_string(),
+        };
+        let res: TournamentEntryResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(Uint128::new(1000), res.entry.chips);
+        assert_eq!(None, res.dealer_score);
+    }
+
+    #[test]
+    fn rounds() {
+        let mut deps = init();
+        let msg = create_msg(None, Some(2));
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        register(&mut deps, mock_env());
+
+        let msg = ExecuteMsg::TournamentBet {
+            tournament_id: 1,
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg);
+        assert_eq!(ContractError::TournamentNotStarted {}, res.unwrap_err());
+
+        let mut env = mock_env();
+        env.block.time = starts_at();
+        let msg = ExecuteMsg::TournamentBet {
+            tournament_id: 1,
+            amount: Uint128::new(1001),
+            seed_commitment: seed_commitment("user0000"),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("user0000", &[]), msg);
+        assert_eq!(
+            ContractError::InsufficientChips {
+                chips: Uint128::new(1000)
+            },
+            res.unwrap_err()
+        );
+        let msg = ExecuteMsg::TournamentAction {
+            tournament_id: 1,
+            action: ActionCommand::Split {
+                amount: Uint128::new(100),
+            },
+            seed: Some(seed("user0000")),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("user0000", &[]), msg);
+        assert_eq!(ContractError::ActionBeforeBet {}, res.unwrap_err());
+
+        let msg = ExecuteMsg::SettleTournament { tournament_id: 1 };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg);
+        assert_eq!(ContractError::TournamentNotOver {}, res.unwrap_err());
+
+        for _ in 0..2 {
+            play(&mut deps, env.clone(), "user0000", 100);
+            play(&mut deps, env.clone(), "user0001", 500);
+        }
+        let msg = ExecuteMsg::TournamentBet {
+            tournament_id: 1,
+            amount: Uint128::new(100),
+            seed_commitment: seed_commitment("user0000"),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("user0000", &[]), msg);
+        assert_eq!(ContractError::TournamentOver {}, res.unwrap_err());
+
+        let standings = get_standings(&deps);
+        assert_eq!(2, standings.len());
+        assert!(standings[0].chips >= standings[1].chips);
+        assert!(standings.iter().all(|s| s.rounds == 2));
+
+        // every player is done, no need to wait for an end time
+        let msg = ExecuteMsg::SettleTournament { tournament_id: 1 };
+        execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        let standings = get_standings(&deps);
+        assert_eq!(Uint128::new(140), standings[0].prize);
+        assert_eq!(Uint128::new(60), standings[1].prize);
+        for standing in standings {
+            assert_eq!(
+                Uint128::new(900) + standing.prize,
+                deposit(&deps, &standing.address)
+            );
+        }
+
+        let msg = ExecuteMsg::SettleTournament { tournament_id: 1 };
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg);
+        assert_eq!(ContractError::TournamentOver {}, res.unwrap_err());
+    }
+
+    #[test]
+    fn idle_entrant() {
+        // rounds only, the second player leaves a hand open and never comes back
+        let mut deps = init();
+        let msg = create_msg(None, Some(2));
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        register(&mut deps, mock_env());
+
+        let mut env = mock_env();
+        env.block.time = starts_at();
+        for _ in 0..2 {
+            play(&mut deps, env.clone(), "user0000", 100);
+        }
+        let msg = ExecuteMsg::TournamentBet {
+            tournament_id: 1,
+            amount: Uint128::new(500),
+            seed_commitment: seed_commitment("user0001"),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("user0001", &[]), msg).unwrap();
+        assert!(!res.attributes.iter().any(|a| a.key == "state"));
+
+        // not over while the open hand may still be played
+        env.block.time = starts_at().plus_seconds(DEFAULT_TIMEOUT - 1);
+        let msg = ExecuteMsg::SettleTournament { tournament_id: 1 };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg);
+        assert_eq!(ContractError::TournamentNotOver {}, res.unwrap_err());
+
+        // done once the turn timed out, the chips at stake are lost
+        env.block.time = starts_at().plus_seconds(DEFAULT_TIMEOUT);
+        let msg = ExecuteMsg::SettleTournament { tournament_id: 1 };
+        execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        let msg = QueryMsg::GetTournamentEntry {
+            tournament_id: 1,
+            address: "user0001".to_string(),
+        };
+        let res: TournamentEntryResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(Uint128::new(500), res.entry.chips);
+        assert_eq!(0, res.entry.rounds);
+        assert!(!res.entry.game.unwrap().ingame);
+        let standings = get_standings(&deps);
+        let prizes: Uint128 = standings.iter().map(|s| s.prize).sum();
+        assert_eq!(Uint128::new(200), prizes);
+
+        // the open hand was closed with the tournament
+        let msg = ExecuteMsg::TournamentAction {
+            tournament_id: 1,
+            action: ActionCommand::Stand,
+            seed: Some(seed("user0001")),
+        };
+        let res = execute(deps.as_mut(), env, mock_info("user0001", &[]), msg);
+        assert_eq!(ContractError::ActionBeforeBet {}, res.unwrap_err());
+    }
+
+    #[test]
+    fn end_time() {
+        let mut deps = init();
+        let ends_at = starts_at().plus_seconds(3600);
+        let msg = create_msg(Some(ends_at), None);
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::RegisterTournament { tournament_id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("user0000", &[]), msg).unwrap();
+
+        // all in, the chips at stake are lost if the round is still open at the end
+        let mut env = mock_env();
+        env.block.time = starts_at();
+        let msg = ExecuteMsg::TournamentBet {
+            tournament_id: 1,
+            amount: Uint128::new(1000),
+            seed_commitment: seed_commitment("user0000"),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("user0000", &[]), msg).unwrap();
+        let open = !res.attributes.iter().any(|a| a.key == "state");
+
+        let msg = ExecuteMsg::SettleTournament { tournament_id: 1 };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg);
+        if open {
+            assert_eq!(ContractError::TournamentNotOver {}, res.unwrap_err());
+
+            env.block.time = ends_at;
+            let msg = ExecuteMsg::TournamentAction {
+                tournament_id: 1,
+                action: ActionCommand::Stand,
+                seed: Some(seed("user0000")),
+            };
+            let res = execute(deps.as_mut(), env.clone(), mock_info("user0000", &[]), msg);
+            assert_eq!(ContractError::TournamentOver {}, res.unwrap_err());
+            let msg = ExecuteMsg::SettleTournament { tournament_id: 1 };
+            execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+        }
+
+        // the only player takes the whole pool
+        let standings = get_standings(&deps);
+        assert_eq!(Uint128::new(100), standings[0].prize);
+        assert_eq!(Uint128::new(1000), deposit(&deps, "user0000"));
+    }
+}
@@ -0,0 +1,465 @@
+use cosmwasm_std::{Addr, Binary, Deps, StdError, StdResult, Storage, Timestamp, Uint128};
+use cw_storage_plus::U64Key;
+
+use crate::card::Hand;
+use crate::msg::VerifyRoundResponse;
+use crate::play::{self, Closing, Rules, Turn};
+use crate::random;
+use crate::shoe::Shoe;
+use crate::state::{
+    GameState, Move, PlayerHand, Round, RoundEntropy, Seat, Table, TablePhase, HISTORY,
+    HOUSE_SEEDS, ROUND_ENTROPY,
+};
+use crate::table::table_seed;
+
+/// Let the dealer play once every hand is closed.
+fn close<T: rand::Rng>(
+    game: &mut GameState,
+    rules: &Rules,
+    turn: Turn,
+    shoe: &mut Shoe,
+    rng: &mut T,
+) {
+    if let Turn::Over(closing) = turn {
+        play::dealer_play(game, rules, shoe, rng, closing);
+        game.ingame = false;
+    }
+}
+
+/// Each house seed must be the one revealed on its index of the chain the house committed to.
+fn check_house_seeds(storage: &dyn Storage, entropy: &RoundEntropy) -> Result<(), String> {
+    for (i, house) in entropy.house_seeds.iter().enumerate() {
+        let revealed = HOUSE_SEEDS
+            .may_load(storage, U64Key::new(house.index))
+            .map_err(|e| e.to_string())?;
+        let previous = HOUSE_SEEDS
+            .may_load(storage, U64Key::new(house.index.wrapping_sub(1)))
+            .map_err(|e| e.to_string())?;
+        let linked = matches!(&previous, Some(p) if random::verify_seed(&house.seed, p));
+        if revealed.as_ref() != Some(&house.seed) || !linked {
+            return Err(format!("house seed {} is not on the house chain", i));
+        }
+    }
+    Ok(())
+}
+
+/// The seeds the players revealed, each checked against its commitment.
+fn player_seeds(entropy: &RoundEntropy) -> Result<Vec<Option<&Binary>>, String> {
+    entropy
+        .players
+        .iter()
+        .map(|p| match &p.seed {
+            Some(seed) if random::verify_seed(seed, &p.seed_commitment) => Ok(Some(seed)),
+            Some(_) => Err("revealed seed does not match the commitment".to_string()),
+            None => Ok(None),
+        })
+        .collect()
+}
+
+/// Rerun the deal and every draw of a game from its entropy, with the steps the
+/// contract dealt them with.
+fn replay_game(entropy: &RoundEntropy) -> Result<(Hand, Vec<Hand>), String> {
+    let player = entropy.players.first().ok_or("the round has no player")?;
+    let player_seed = player_seeds(entropy)?[0];
+
+    let rules = Rules::of_round(entropy);
+    let mut shoe = entropy.shoe.clone();
+    // a round the house never dealt has nothing to draw
+    let mut game = GameState {
+        ingame: true,
+        player_hands: vec![PlayerHand::default()],
+        ..GameState::default()
+    };
+    for (i, house) in entropy.house_seeds.iter().enumerate() {
+        let dealt = !game.dealer_hand.is_empty();
+        match &house.made_for {
+            Move::Deal if !dealt => {
+                let mut rng = random::gen_rng(&player.seed_commitment, &house.seed, house.made_at);
+                let turn = play::deal(&mut game, &rules, &mut shoe, &mut rng);
+                close(&mut game, &rules, turn, &mut shoe, &mut rng);
+            }
+            Move::Action(action) if dealt && game.ingame => {
+                let seed = player_seed.ok_or("the seed was never revealed")?;
+                let mut rng = random::gen_rng(seed, &house.seed, house.made_at);
+                let turn =
+                    if play::declined_into_blackjack(&mut game, &rules, action, &shoe, &mut rng) {
+                        Turn::Over(Closing::Stand)
+                    } else {
+                        play::act(&mut game, action, &mut shoe, &mut rng)
+                    };
+                close(&mut game, &rules, turn, &mut shoe, &mut rng);
+            }
+            Move::ForceSettle(timeout_action) if dealt && game.ingame => {
+                // the commitment stands in until the seed is revealed with an action
+                let acted = entropy.house_seeds[..i]
+                    .iter()
+                    .any(|h| matches!(h.made_for, Move::Action(_)));
+                let seed = match player_seed {
+                    Some(seed) if acted => seed,
+                    _ => &player.seed_commitment,
+                };
+                let mut rng = random::gen_rng(seed, &house.seed, house.made_at);
+                let turn = Turn::Over(Closing::from(*timeout_action));
+                close(&mut game, &rules, turn, &mut shoe, &mut rng);
+            }
+            _ => return Err(format!("house seed {} is out of place", i)),
+        }
+    }
+
+    // a round still open was closed without drawing, by a forfeit or a house timeout
+    let player_hands = game.player_hands.into_iter().map(|h| h.cards).collect();
+    Ok((game.dealer_hand, player_hands))
+}
+
+/// Rerun a table round from its entropy, every seat in play in seat order,
+/// and return the dealer's and `address`'s cards.
+fn replay_table(
+    entropy: &RoundEntropy,
+    round: &Round,
+    address: &Addr,
+) -> Result<(Hand, Vec<Hand>), String> {
+    let player_seeds = player_seeds(entropy)?;
+    let own = entropy
+        .players
+        .iter()
+        .position(|p| p.player == *address)
+        .ok_or("the player has no seat in the round")?;
+
+    let rules = Rules::of_round(entropy);
+    let mut shoe = entropy.shoe.clone();
+    let mut table = Table {
+        token: round.token.clone(),
+        // any stake puts a seat in play
+        seats: entropy
+            .players
+            .iter()
+            .map(|p| Seat {
+                player: p.player.clone(),
+                bet_amount: Uint128::new(1),
+                cards: vec![],
+                seed_commitment: p.seed_commitment.clone(),
+                player_seed: None,
+                liability: Uint128::zero(),
+            })
+            .collect(),
+        phase: TablePhase::Playing,
+        betting_window: 0,
+        turn_timeout: 0,
+        betting_ends: None,
+        dealer_hand: vec![],
+        dealer_blackjack: None,
+        active_seat: 0,
+        turn_started: Timestamp::default(),
+        pending: None,
+    };
+    let mut over = false;
+    for (i, house) in entropy.house_seeds.iter().enumerate() {
+        let dealt = !table.dealer_hand.is_empty();
+        match house.made_for {
+            Move::Deal if !dealt => {}
+            Move::Action(_) | Move::ForceSettle(_) if dealt && !over => {}
+            _ => return Err(format!("house seed {} is out of place", i)),
+        }
+        let active = table.active_seat as usize;
+        if let Move::Action(_) = house.made_for {
+            // the seat reveals its seed with the action
+            let seed = player_seeds[active].ok_or("the seed was never revealed")?;
+            table.seats[active].player_seed = Some(seed.clone());
+        }
+
+        let mut rng = random::gen_rng(&table_seed(&table), &house.seed, house.made_at);
+        let next = match &house.made_for {
+            Move::Deal => play::deal_table(&mut table, &mut shoe, &mut rng),
+            Move::Action(action) => play::act_seat(&mut table, action, &mut shoe, &mut rng),
+            Move::ForceSettle(_) => play::next_seat(&mut table),
+        };
+        if next.is_none() {
+            play::dealer_play_table(&mut table, &rules, &mut shoe, &mut rng);
+            over = true;
+        }
+    }
+
+    // a round still open was closed by a house timeout
+    let cards = std::mem::take(&mut table.seats[own].cards);
+    Ok((table.dealer_hand, vec![cards]))
+}
+
+/// Games and table rounds can be replayed.
+/// Tournament hands are not kept as rounds, so they have nothing to verify.
+pub fn query_verify_round(
+    deps: Deps,
+    address: String,
+    round_id: u64,
+) -> StdResult<VerifyRoundResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let round = HISTORY.load(deps.storage, (&address, U64Key::new(round_id)))?;
+    let entropy = ROUND_ENTROPY
+        .may_load(deps.storage, (&address, U64Key::new(round_id)))?
+        .ok_or_else(|| {
+            StdError::generic_err(format!(
+                "round {} was played before its entropy was kept",
+                round_id
+            ))
+        })?;
+
+    let replayed = check_house_seeds(deps.storage, &entropy).and_then(|_| {
+        if entropy.table_id.is_some() {
+            replay_table(&entropy, &round, &address)
+        } else {
+            replay_game(&entropy)
+        }
+    });
+    let (dealer_hand, player_hands, error) = match replayed {
+        Ok((dealer_hand, player_hands)) => (dealer_hand, player_hands, None),
+        Err(error) => (vec![], vec![], Some(error)),
+    };
+    let verified = error.is_none()
+        && dealer_hand == round.dealer_hand
+        && player_hands
+            .iter()
+            .eq(round.player_hands.iter().map(|h| &h.cards));
+
+    Ok(VerifyRoundResponse {
+        round_id,
+        entropy,
+        dealer_hand,
+        player_hands,
+        verified,
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::query;
+    use crate::msg::{ActionCommand, ExecuteMsg, GameStateResponce, InstantiateMsg, QueryMsg};
+    use crate::state::HOUSE_CHAIN;
+    use crate::testing::{self, execute, instantiate_msg, TestDeps};
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::{from_binary, Addr, Binary, Uint128};
+
+    const PLAYER: &str = "user0000";
+
+    /// A fresh seed each round.
+    fn seed(round: u64) -> Binary {
+        testing::seed(&format!("{} {}", PLAYER, round))
+    }
+
+    fn init() -> TestDeps {
+        let mut deps = testing::init(InstantiateMsg {
+            insurance: Some(true),
+            ..instantiate_msg()
+        });
+        testing::deposit(&mut deps, PLAYER, 10_000);
+        testing::provide_liquidity(&mut deps, 1_000_000);
+
+        deps
+    }
+
+    fn in_game(deps: &TestDeps) -> bool {
+        let msg = QueryMsg::GetGameState {
+            address: PLAYER.to_string(),
+            token: testing::token("token0000"),
+        };
+        let res: GameStateResponce =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.state.ingame
+    }
+
+    fn verify(deps: &TestDeps, round_id: u64) -> VerifyRoundResponse {
+        verify_player(deps, PLAYER, round_id)
+    }
+
+    fn verify_player(deps: &TestDeps, player: &str, round_id: u64) -> VerifyRoundResponse {
+        let msg = QueryMsg::VerifyRound {
+            address: player.to_string(),
+            round_id,
+        };
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+    }
+
+    /// Rounds hit once, played to the end, or left to time out, each in a later block.
+    #[test]
+    fn rounds() {
+        let mut deps = init();
+        let mut env = mock_env();
+        for round in 1..=12u64 {
+            env.block.height += 1;
+            env.block.time = env.block.time.plus_seconds(5);
+            let msg = ExecuteMsg::Bet {
+                token: testing::token("token0000"),
+                amount: Uint128::new(100),
+                seed_commitment: Binary(random::sha256(&seed(round))),
+                perfect_pairs: None,
+                twenty_one_plus_three: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(PLAYER, &[]), msg).unwrap();
+
+            // every third round is left to time out before the seed is revealed
+            let actions = if round % 3 == 0 {
+                vec![]
+            } else {
+                vec![ActionCommand::Hit, ActionCommand::Stand]
+            };
+            for action in actions {
+                if !in_game(&deps) {
+                    break;
+                }
+                env.block.height += 1;
+                let msg = ExecuteMsg::Action {
+                    token: testing::token("token0000"),
+                    action,
+                    seed: Some(seed(round)),
+                };
+                execute(deps.as_mut(), env.clone(), mock_info(PLAYER, &[]), msg).unwrap();
+            }
+            if in_game(&deps) {
+                env.block.time = env.block.time.plus_seconds(86_400);
+                let msg = ExecuteMsg::ForceSettle {
+                    address: PLAYER.to_string(),
+                    token: testing::token("token0000"),
+                };
+                execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+            }
+
+            let res = verify(&deps, round);
+            assert_eq!(None, res.error);
+            assert!(res.verified);
+            let chain = HOUSE_CHAIN.load(&deps.storage).unwrap();
+            assert_eq!(
+                chain.next - 1,
+                res.entropy.house_seeds.last().unwrap().index
+            );
+        }
+    }
+
+    #[test]
+    fn tampered() {
+        let mut deps = init();
+        let msg = ExecuteMsg::Bet {
+            token: testing::token("token0000"),
+            amount: Uint128::new(100),
+            seed_commitment: Binary(random::sha256(&seed(1))),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(PLAYER, &[]), msg).unwrap();
+        if in_game(&deps) {
+            let msg = ExecuteMsg::Action {
+                token: testing::token("token0000"),
+                action: ActionCommand::Stand,
+                seed: Some(seed(1)),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(PLAYER, &[]), msg).unwrap();
+        }
+        assert!(verify(&deps, 1).verified);
+
+        let player = Addr::unchecked(PLAYER);
+        let key = (&player, U64Key::new(1));
+        let mut round = HISTORY.load(&deps.storage, key.clone()).unwrap();
+        let dealt = round.dealer_hand.clone();
+        round.dealer_hand.swap(0, 1);
+        HISTORY
+            .save(&mut deps.storage, key.clone(), &round)
+            .unwrap();
+        let res = verify(&deps, 1);
+        assert_eq!(None, res.error);
+        assert_eq!(dealt, res.dealer_hand);
+        assert!(!res.verified);
+
+        let mut entropy = ROUND_ENTROPY.load(&deps.storage, key.clone()).unwrap();
+        entropy.house_seeds[0].seed = testing::house_seed(2);
+        ROUND_ENTROPY
+            .save(&mut deps.storage, key, &entropy)
+            .unwrap();
+        let res = verify(&deps, 1);
+        assert_eq!(
+            Some("house seed 0 is not on the house chain".to_string()),
+            res.error
+        );
+        assert!(!res.verified);
+    }
+
+    /// Table rounds of two seats, each hitting once then standing, or left to be made to stand.
+    #[test]
+    fn table_rounds() {
+        let players = ["user0000", "user0001"];
+        let mut deps = init();
+        testing::deposit(&mut deps, players[1], 10_000);
+        let msg = ExecuteMsg::OpenTable {
+            token: testing::token("token0000"),
+            betting_window: None,
+            turn_timeout: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        for player in players {
+            let msg = ExecuteMsg::JoinTable { table_id: 1 };
+            execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg).unwrap();
+        }
+
+        let mut env = mock_env();
+        for round in 1..=6u64 {
+            for player in players {
+                let msg = ExecuteMsg::TableBet {
+                    table_id: 1,
+                    amount: Uint128::new(100),
+                    seed_commitment: testing::seed_commitment(&format!("{} {}", player, round)),
+                };
+                execute(deps.as_mut(), env.clone(), mock_info(player, &[]), msg).unwrap();
+            }
+            let msg = ExecuteMsg::DealTable { table_id: 1 };
+            let mut res =
+                execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+
+            let mut acted = vec![];
+            while let Some(seat) = res.attributes.iter().rev().find(|a| a.key == "seat") {
+                let seat: usize = seat.value.parse().unwrap();
+                let player = players[seat];
+                res = if round % 3 == 0 && seat == 1 {
+                    // the second seat is left to time out every third round
+                    env.block.time = env
+                        .block
+                        .time
+                        .plus_seconds(crate::table::DEFAULT_TURN_TIMEOUT);
+                    let msg = ExecuteMsg::ForceStand { table_id: 1 };
+                    execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap()
+                } else {
+                    let action = if acted.contains(&seat) {
+                        ActionCommand::Stand
+                    } else {
+                        ActionCommand::Hit
+                    };
+                    acted.push(seat);
+                    let msg = ExecuteMsg::TableAction {
+                        table_id: 1,
+                        action,
+                        seed: Some(testing::seed(&format!("{} {}", player, round))),
+                    };
+                    execute(deps.as_mut(), env.clone(), mock_info(player, &[]), msg).unwrap()
+                };
+                if res
+                    .attributes
+                    .iter()
+                    .any(|a| a.key == "state" && a.value == "end")
+                {
+                    break;
+                }
+            }
+
+            let round_ids = &res
+                .attributes
+                .iter()
+                .find(|a| a.key == "round_id")
+                .unwrap()
+                .value;
+            for (player, round_id) in players.iter().zip(round_ids.split(", ")) {
+                let res = verify_player(&deps, player, round_id.parse().unwrap());
+                assert_eq!(None, res.error);
+                assert!(res.verified);
+                assert_eq!(Some(1), res.entropy.table_id);
+                assert_eq!(2, res.entropy.players.len());
+            }
+        }
+    }
+}
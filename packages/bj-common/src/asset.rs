@@ -0,0 +1,111 @@
+use std::fmt;
+
+use cosmwasm_std::{Addr, StdError, StdResult};
+use cw_storage_plus::{Prefixer, PrimaryKey};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const TOKEN: &[u8] = b"token";
+const NATIVE_TOKEN: &[u8] = b"native_token";
+
+/// A cw20 token or a bank denom.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetInfo {
+    Token { contract_addr: Addr },
+    NativeToken { denom: String },
+}
+
+impl AssetInfo {
+    pub fn cw20(contract_addr: &Addr) -> Self {
+        AssetInfo::Token {
+            contract_addr: contract_addr.clone(),
+        }
+    }
+
+    pub fn native(denom: &str) -> Self {
+        AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        }
+    }
+
+    /// Reads back an asset from the key of a map keyed by assets,
+    /// as found in a range over the map.
+    pub fn from_key(key: &[u8]) -> StdResult<Self> {
+        let invalid = || StdError::generic_err("invalid asset key");
+        if key.len() < 2 {
+            return Err(invalid());
+        }
+        let len = u16::from_be_bytes([key[0], key[1]]) as usize;
+        if key.len() < 2 + len {
+            return Err(invalid());
+        }
+        let (kind, id) = key[2..].split_at(len);
+        let id = String::from_utf8(id.to_vec()).map_err(|_| StdError::invalid_utf8("asset key"))?;
+        match kind {
+            TOKEN => Ok(AssetInfo::Token {
+                contract_addr: Addr::unchecked(id),
+            }),
+            NATIVE_TOKEN => Ok(AssetInfo::NativeToken { denom: id }),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn kind(&self) -> &'static [u8] {
+        match self {
+            AssetInfo::Token { .. } => TOKEN,
+            AssetInfo::NativeToken { .. } => NATIVE_TOKEN,
+        }
+    }
+
+    fn id(&self) -> &[u8] {
+        match self {
+            AssetInfo::Token { contract_addr } => contract_addr.as_bytes(),
+            AssetInfo::NativeToken { denom } => denom.as_bytes(),
+        }
+    }
+}
+
+impl fmt::Display for AssetInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetInfo::Token { contract_addr } => write!(f, "{}", contract_addr),
+            AssetInfo::NativeToken { denom } => write!(f, "{}", denom),
+        }
+    }
+}
+
+/// Keyed by the kind of asset, then its address or denom,
+/// so a denom never shares a key with a cw20 address of the same bytes.
+impl<'a> PrimaryKey<'a> for &'a AssetInfo {
+    type Prefix = ();
+    type SubPrefix = ();
+
+    fn key(&self) -> Vec<&[u8]> {
+        vec![self.kind(), self.id()]
+    }
+}
+
+impl<'a> Prefixer<'a> for &'a AssetInfo {
+    fn prefix(&self) -> Vec<&[u8]> {
+        vec![self.kind(), self.id()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key() {
+        let token = AssetInfo::cw20(&Addr::unchecked("uluna"));
+        let native = AssetInfo::native("uluna");
+        assert_ne!((&token).joined_key(), (&native).joined_key());
+
+        for asset in [token, native] {
+            assert_eq!(asset, AssetInfo::from_key(&(&asset).joined_key()).unwrap());
+        }
+        AssetInfo::from_key(b"\x00\x05cw721uluna").unwrap_err();
+        AssetInfo::from_key(b"\x00\x20token").unwrap_err();
+    }
+}
@@ -0,0 +1,5 @@
+mod asset;
+mod version;
+
+pub use crate::asset::AssetInfo;
+pub use crate::version::parse_version;
@@ -0,0 +1,30 @@
+/// Splits "major.minor.patch" for comparison. Pre-release and build tags are ignored.
+pub fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    let parts = core
+        .split('.')
+        .map(|p| p.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    match parts[..] {
+        [major, minor, patch] => Some((major, minor, patch)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version() {
+        assert_eq!(Some((0, 1, 0)), parse_version("0.1.0"));
+        assert_eq!(Some((1, 12, 3)), parse_version("1.12.3-beta.1"));
+        assert_eq!(Some((2, 0, 0)), parse_version("2.0.0+build.5"));
+        assert!(parse_version("0.1.0") < parse_version("0.2.0"));
+        assert!(parse_version("0.10.0") > parse_version("0.9.9"));
+        assert_eq!(None, parse_version("0.1"));
+        assert_eq!(None, parse_version("latest"));
+        assert_eq!(None, parse_version("1.x.0"));
+    }
+}